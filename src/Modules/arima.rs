@@ -1,225 +1,1153 @@
-use super::data_struct::{Data, extract_series, read_csv};
+use super::data_struct::{Dataset, extract_series, read_csv};
+use super::embedded;
+use super::error::{Error, Result};
+use super::optim::{NelderMeadOptions, nelder_mead};
+use super::stats::ols;
 use nalgebra::{DMatrix, DVector};
+use serde::{Deserialize, Serialize, Serializer};
 use statrs::distribution::{ContinuousCDF, Normal};
-use std::error::Error;
+use std::collections::VecDeque;
 
 // Differencing function for the time series
 fn difference(series: &[f64], order: usize) -> Vec<f64> {
-    if order == 0 {
-        return series.to_vec();
+    let mut values = series.to_vec();
+    let len = embedded::difference_in_place(&mut values, order);
+    values.truncate(len);
+    values
+}
+
+// Seasonal differencing (1 - B^s)^D
+fn seasonal_difference(series: &[f64], period: usize, order: usize) -> Vec<f64> {
+    let mut values = series.to_vec();
+    let len = embedded::seasonal_difference_in_place(&mut values, period, order);
+    values.truncate(len);
+    values
+}
+
+// Coefficients delta_k of (1 - B)^d (1 - B^s)^D = 1 - sum delta_k B^k, so
+// the original series is recovered as y_t = w_t + sum delta_k y_{t-k}
+fn integration_coeffs(d: usize, seasonal: Option<SeasonalOrder>) -> Vec<f64> {
+    let (seasonal_d, period) = seasonal.map_or((0, 0), |s| (s.d, s.period));
+    let mut delta = vec![0.0; d + seasonal_d * period];
+    embedded::integration_coeffs_into(d, seasonal_d, period, &mut delta);
+    delta
+}
+
+// Multiplicative polynomial (1 - sum phi_i B^i)(1 - sum Phi_j B^{js}) written
+// back as 1 - sum a_k B^k. With `sign = -1.0` it expands the MA polynomial
+// (1 + sum theta_i B^i)(1 + sum Theta_j B^{js}) instead.
+fn expand_polynomial(regular: &[f64], seasonal: &[f64], period: usize, sign: f64) -> Vec<f64> {
+    let mut full = vec![0.0; regular.len() + seasonal.len() * period];
+    full[..regular.len()].copy_from_slice(regular);
+    for (j, big) in seasonal.iter().enumerate() {
+        let lag = (j + 1) * period;
+        full[lag - 1] += big;
+        for (i, small) in regular.iter().enumerate() {
+            full[lag + i] += sign * small * big;
+        }
     }
+    full
+}
 
-    let diff_series: Vec<f64> = series.windows(2).map(|w| w[1] - w[0]).collect();
-    if order == 1 {
-        return diff_series;
+// Map unconstrained parameters to the coefficients of a stationary AR
+// polynomial: tanh gives partial autocorrelations in (-1, 1) and the
+// Durbin-Levinson recursion turns them into AR coefficients (Jones, 1980)
+fn pacf_to_coeffs(raw: &[f64]) -> Vec<f64> {
+    let mut work: Vec<f64> = raw.iter().map(|r| r.tanh()).collect();
+    let mut coeffs = work.clone();
+
+    for j in 1..coeffs.len() {
+        let a = coeffs[j];
+        for k in 0..j {
+            work[k] -= a * coeffs[j - k - 1];
+        }
+        coeffs[..j].copy_from_slice(&work[..j]);
     }
 
-    difference(&diff_series, order - 1)
+    coeffs
+}
+
+// Coefficients decoded from one parameter vector of the optimizer
+struct ArmaParams {
+    ar: Vec<f64>,
+    ma: Vec<f64>,
+    seasonal_ar: Vec<f64>,
+    seasonal_ma: Vec<f64>,
+    mean: f64,
+    exog: Vec<f64>,
 }
 
-// Auto-regressive model estimation
-fn fit_ar(series: &[f64], p: usize) -> Vec<f64> {
-    // Create matrices for linear regression
-    let n = series.len() - p;
+impl ArmaParams {
+    // Parameter layout: [phi, theta, Phi, Theta, mean?, beta]. MA terms reuse
+    // the AR transform with a sign flip, which keeps 1 + theta(B) invertible.
+    fn unpack(params: &[f64], spec: &ArimaSpec, include_mean: bool) -> ArmaParams {
+        let (sp, sq) = spec.seasonal.map_or((0, 0), |s| (s.p, s.q));
+        let mut offset = 0;
+        let mut take = |len: usize, negate: bool| {
+            let coeffs = pacf_to_coeffs(&params[offset..offset + len]);
+            offset += len;
+            if negate {
+                coeffs.into_iter().map(|c| -c).collect()
+            } else {
+                coeffs
+            }
+        };
+        let ar = take(spec.p, false);
+        let ma = take(spec.q, true);
+        let seasonal_ar = take(sp, false);
+        let seasonal_ma = take(sq, true);
+        let mean = if include_mean { params[offset] } else { 0.0 };
+        ArmaParams {
+            ar,
+            ma,
+            seasonal_ar,
+            seasonal_ma,
+            mean,
+            exog: params[offset + include_mean as usize..].to_vec(),
+        }
+    }
 
-    let mut y = Vec::with_capacity(n);
-    let mut x = Vec::with_capacity(n * p);
+    // Full AR and MA polynomials of the multiplicative seasonal model
+    fn expanded(&self, spec: &ArimaSpec) -> (Vec<f64>, Vec<f64>) {
+        let period = spec.seasonal.map_or(0, |s| s.period);
+        (
+            expand_polynomial(&self.ar, &self.seasonal_ar, period, -1.0),
+            expand_polynomial(&self.ma, &self.seasonal_ma, period, 1.0),
+        )
+    }
+}
 
-    for i in p..series.len() {
-        y.push(series[i]);
+// Conditional residuals: pre-sample residuals are set to zero and the
+// recursion starts once p lags are available
+fn css_residuals(series: &[f64], ar: &[f64], ma: &[f64], mean: f64) -> Vec<f64> {
+    let mut residuals = vec![0.0; series.len()];
+    embedded::css_residuals(series, ar, ma, mean, &mut residuals);
+    residuals.split_off(ar.len().min(series.len()))
+}
 
-        for j in 0..p {
-            x.push(series[i - j - 1]);
+// Initial state covariance of a stationary ARMA in state space form:
+// solves P = T P T' + R R' with the doubling algorithm
+fn stationary_covariance(ar: &[f64], r_vec: &[f64]) -> Vec<f64> {
+    let r = r_vec.len();
+    let mut p_mat: Vec<f64> = (0..r * r).map(|k| r_vec[k / r] * r_vec[k % r]).collect();
+    let mut a_mat = vec![0.0; r * r];
+    for i in 0..r {
+        a_mat[i * r] = ar.get(i).copied().unwrap_or(0.0);
+        if i + 1 < r {
+            a_mat[i * r + i + 1] = 1.0;
         }
     }
 
-    let y_vec = DVector::from_vec(y);
-    let x_mat = DMatrix::from_vec(n, p, x);
+    let matmul = |x: &[f64], y: &[f64]| -> Vec<f64> {
+        let mut out = vec![0.0; r * r];
+        for i in 0..r {
+            for k in 0..r {
+                let xik = x[i * r + k];
+                if xik != 0.0 {
+                    for j in 0..r {
+                        out[i * r + j] += xik * y[k * r + j];
+                    }
+                }
+            }
+        }
+        out
+    };
+    let transpose =
+        |x: &[f64]| -> Vec<f64> { (0..r * r).map(|k| x[(k % r) * r + k / r]).collect() };
 
-    // Solve linear equation to get AR coefficients
-    match x_mat.transpose() * &x_mat {
-        mat if mat.is_invertible() => {
-            let x_inv = mat.try_inverse().unwrap();
-            let coeffs = x_inv * (x_mat.transpose() * y_vec);
-            coeffs.as_slice().to_vec()
+    for _ in 0..100 {
+        let increment = matmul(&matmul(&a_mat, &p_mat), &transpose(&a_mat));
+        let change = increment.iter().fold(0.0_f64, |m, v| m.max(v.abs()));
+        for (p, inc) in p_mat.iter_mut().zip(&increment) {
+            *p += inc;
         }
-        _ => {
-            // Fallback if matrix is not invertible
-            vec![0.0; p]
+        a_mat = matmul(&a_mat, &a_mat);
+        if change < 1e-14 * p_mat[0].abs().max(1.0) {
+            break;
         }
     }
+
+    p_mat
+}
+
+// Last `len` values of a slice, padded at the front with `pad` when the
+// history is shorter than the number of lags
+fn tail(values: &[f64], len: usize, pad: f64) -> VecDeque<f64> {
+    let mut out: VecDeque<f64> = values[values.len().saturating_sub(len)..]
+        .iter()
+        .copied()
+        .collect();
+    while out.len() < len {
+        out.push_front(pad);
+    }
+    out
 }
 
-// Moving average model estimation
-fn fit_ma(residuals: &[f64], q: usize) -> Vec<f64> {
-    // Simplified MA estimation using correlation
-    let mut coeffs = vec![0.0; q];
+// State of the forecast recursion at the current origin. It keeps only what
+// the recursion needs: one level of the (noise) series per differencing lag,
+// one differenced value per AR lag and one innovation per MA lag, newest last.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ForecastOrigin {
+    delta: Vec<f64>,
+    levels: VecDeque<f64>,
+    values: VecDeque<f64>,
+    residuals: VecDeque<f64>,
+}
 
-    if residuals.len() <= q {
-        return coeffs;
+impl ForecastOrigin {
+    fn new(
+        delta: Vec<f64>,
+        levels: &[f64],
+        values: &[f64],
+        residuals: &[f64],
+        ar_lags: usize,
+        ma_lags: usize,
+        mean: f64,
+    ) -> Self {
+        ForecastOrigin {
+            levels: tail(levels, delta.len(), 0.0),
+            values: tail(values, ar_lags, mean),
+            residuals: tail(residuals, ma_lags, 0.0),
+            delta,
+        }
     }
 
-    let mean = residuals.iter().sum::<f64>() / residuals.len() as f64;
-    let variance =
-        residuals.iter().map(|&r| (r - mean).powi(2)).sum::<f64>() / residuals.len() as f64;
+    // One-step prediction of the differenced series
+    fn predict_value(&self, ar: &[f64], ma: &[f64], mean: f64) -> f64 {
+        let mut prediction = mean;
+        for (coeff, lagged) in ar.iter().zip(self.values.iter().rev()) {
+            prediction += coeff * (lagged - mean);
+        }
+        for (coeff, residual) in ma.iter().zip(self.residuals.iter().rev()) {
+            prediction += coeff * residual;
+        }
+        prediction
+    }
 
-    for i in 0..q {
-        if i < residuals.len() - 1 {
-            let lag_covariance = residuals
-                .windows(2)
-                .map(|w| (w[0] - mean) * (w[1] - mean))
-                .sum::<f64>()
-                / (residuals.len() - 1) as f64;
-            coeffs[i] = lag_covariance / variance;
+    // Undo regular and seasonal differencing at once for the next time step:
+    // y_t = w_t + sum delta_k y_{t-k}
+    fn integrate(&self, value: f64) -> f64 {
+        let mut level = value;
+        for (coeff, lagged) in self.delta.iter().zip(self.levels.iter().rev()) {
+            level += coeff * lagged;
         }
+        level
     }
 
-    coeffs
+    // Move the origin one step forward
+    fn advance(&mut self, level: f64, value: f64, residual: f64) {
+        for (buffer, new) in [
+            (&mut self.levels, level),
+            (&mut self.values, value),
+            (&mut self.residuals, residual),
+        ] {
+            if !buffer.is_empty() {
+                buffer.pop_front();
+                buffer.push_back(new);
+            }
+        }
+    }
+
+    // Multi-step forecast: each prediction is fed back as an AR lag and as a
+    // level for the inverse differencing, with future innovations set to zero
+    fn forecast(&self, ar: &[f64], ma: &[f64], mean: f64, steps: usize) -> Vec<f64> {
+        let mut origin = self.clone();
+        (0..steps)
+            .map(|_| {
+                let value = origin.predict_value(ar, ma, mean);
+                let level = origin.integrate(value);
+                origin.advance(level, value, 0.0);
+                level
+            })
+            .collect()
+    }
+}
+
+// Output of the Kalman filter run over a (demeaned) ARMA series
+struct KalmanOutput {
+    log_likelihood: f64,
+    sigma2: f64,
+    innovations: Vec<f64>,
+}
+
+// Exact Gaussian likelihood of an ARMA(p, q) through the Kalman filter,
+// with sigma^2 concentrated out. State dimension is max(p, q + 1).
+fn kalman_likelihood(series: &[f64], ar: &[f64], ma: &[f64], mean: f64) -> KalmanOutput {
+    let r = ar.len().max(ma.len() + 1);
+    let phi: Vec<f64> = (0..r).map(|i| ar.get(i).copied().unwrap_or(0.0)).collect();
+    let r_vec: Vec<f64> = (0..r)
+        .map(|i| {
+            if i == 0 {
+                1.0
+            } else {
+                ma.get(i - 1).copied().unwrap_or(0.0)
+            }
+        })
+        .collect();
+
+    let mut a = vec![0.0; r];
+    let mut p_mat = stationary_covariance(&phi, &r_vec);
+    let mut steady = false;
+
+    let n = series.len();
+    let mut innovations = Vec::with_capacity(n);
+    let mut sum_squares = 0.0;
+    let mut sum_log_f = 0.0;
+
+    for &y in series {
+        let v = y - mean - a[0];
+        let f = p_mat[0].max(1e-12);
+        innovations.push(v);
+        sum_squares += v * v / f;
+        sum_log_f += f.ln();
+
+        // Filtered state a + P Z' v / F, then one step ahead with T
+        let gain: Vec<f64> = (0..r).map(|i| p_mat[i * r] / f).collect();
+        let filtered: Vec<f64> = (0..r).map(|i| a[i] + gain[i] * v).collect();
+        for i in 0..r {
+            a[i] = phi[i] * filtered[0] + if i + 1 < r { filtered[i + 1] } else { 0.0 };
+        }
+
+        if steady {
+            continue;
+        }
+
+        // P_{t|t} = P - P Z' Z P / F, then P_{t+1} = T P_{t|t} T' + R R'
+        let mut updated = p_mat.clone();
+        for i in 0..r {
+            for j in 0..r {
+                updated[i * r + j] -= p_mat[i * r] * p_mat[j * r] / f;
+            }
+        }
+        let mut tp = vec![0.0; r * r];
+        for i in 0..r {
+            for j in 0..r {
+                let next = if i + 1 < r {
+                    updated[(i + 1) * r + j]
+                } else {
+                    0.0
+                };
+                tp[i * r + j] = phi[i] * updated[j] + next;
+            }
+        }
+        let mut next_p = vec![0.0; r * r];
+        for i in 0..r {
+            for j in 0..r {
+                let next = if j + 1 < r { tp[i * r + j + 1] } else { 0.0 };
+                next_p[i * r + j] = tp[i * r] * phi[j] + next + r_vec[i] * r_vec[j];
+            }
+        }
+
+        // Once the covariance stops changing the filter has reached its
+        // steady state and the remaining steps only update the mean
+        let change = next_p
+            .iter()
+            .zip(&p_mat)
+            .fold(0.0_f64, |m, (x, y)| m.max((x - y).abs()));
+        steady = change < 1e-12;
+        p_mat = next_p;
+    }
+
+    let sigma2 = sum_squares / n as f64;
+    let log_likelihood =
+        -0.5 * (n as f64 * ((2.0 * std::f64::consts::PI * sigma2).ln() + 1.0) + sum_log_f);
+
+    KalmanOutput {
+        log_likelihood,
+        sigma2,
+        innovations,
+    }
+}
+
+// Prediction interval at one confidence level
+#[derive(Debug, Clone)]
+pub struct PredictionInterval {
+    pub level: f64,
+    pub lower: Vec<f64>,
+    pub upper: Vec<f64>,
+}
+
+// Point forecasts with standard errors and prediction intervals
+#[derive(Debug, Clone)]
+pub struct IntervalForecast {
+    pub mean: Vec<f64>,
+    pub std_error: Vec<f64>,
+    pub intervals: Vec<PredictionInterval>,
+}
+
+impl IntervalForecast {
+    // Gaussian intervals mean +/- z * std_error at each confidence level
+    pub fn gaussian(mean: Vec<f64>, std_error: Vec<f64>, levels: &[f64]) -> Result<Self> {
+        if let Some(level) = levels.iter().find(|l| !(**l > 0.0 && **l < 1.0)) {
+            return Err(Error::Model(format!(
+                "Confidence level must be in (0, 1), got {}",
+                level
+            )));
+        }
+        let normal = Normal::new(0.0, 1.0).map_err(|e| Error::Model(e.to_string()))?;
+        let intervals = levels
+            .iter()
+            .map(|&level| {
+                let z = normal.inverse_cdf(0.5 + level / 2.0);
+                PredictionInterval {
+                    level,
+                    lower: mean
+                        .iter()
+                        .zip(&std_error)
+                        .map(|(m, s)| m - z * s)
+                        .collect(),
+                    upper: mean
+                        .iter()
+                        .zip(&std_error)
+                        .map(|(m, s)| m + z * s)
+                        .collect(),
+                }
+            })
+            .collect();
+        Ok(IntervalForecast {
+            mean,
+            std_error,
+            intervals,
+        })
+    }
+
+    // Interval for a given level, e.g. 0.95
+    pub fn interval(&self, level: f64) -> Option<&PredictionInterval> {
+        self.intervals
+            .iter()
+            .find(|i| (i.level - level).abs() < 1e-9)
+    }
+}
+
+// Recursive least squares state used to adapt the ARMA coefficients online
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RlsState {
+    forgetting: f64,
+    covariance: Vec<f64>, // (p + q) x (p + q), row major
+}
+
+// Everything needed to run the one-step predictor outside this crate, e.g.
+// in generated microcontroller code. Buffers are ordered oldest first.
+#[derive(Debug, Clone)]
+pub struct PredictorParts {
+    pub ar: Vec<f64>,        // Expanded AR polynomial (seasonal terms included)
+    pub ma: Vec<f64>,        // Expanded MA polynomial
+    pub delta: Vec<f64>,     // Inverse differencing coefficients
+    pub mean: f64,           // Mean of the differenced series
+    pub levels: Vec<f64>,    // Last levels, one per differencing lag
+    pub values: Vec<f64>,    // Last differenced values, one per AR lag
+    pub residuals: Vec<f64>, // Last innovations, one per MA lag
+}
+
+// Kalman filter cost grows with the square of the state dimension; long
+// seasonal periods (8640 samples per day at 10 s) must be resampled first
+const MAX_STATE_DIMENSION: usize = 400;
+
+// Seasonal part (P, D, Q) with period s of a SARIMA model
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SeasonalOrder {
+    pub p: usize,
+    pub d: usize,
+    pub q: usize,
+    pub period: usize,
+}
+
+impl SeasonalOrder {
+    pub fn new(p: usize, d: usize, q: usize, period: usize) -> Self {
+        SeasonalOrder { p, d, q, period }
+    }
+}
+
+// Orders of a SARIMA(p,d,q)(P,D,Q)s model
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ArimaSpec {
+    pub p: usize,
+    pub d: usize,
+    pub q: usize,
+    pub seasonal: Option<SeasonalOrder>,
+}
+
+impl ArimaSpec {
+    pub fn new(p: usize, d: usize, q: usize) -> Self {
+        ArimaSpec {
+            p,
+            d,
+            q,
+            seasonal: None,
+        }
+    }
+
+    pub fn with_seasonal(mut self, seasonal: SeasonalOrder) -> Self {
+        self.seasonal = Some(seasonal);
+        self
+    }
+
+    // Differencing applied before the ARMA part: regular, then seasonal
+    fn differenced(&self, series: &[f64]) -> Vec<f64> {
+        let regular = difference(series, self.d);
+        match self.seasonal {
+            Some(s) => seasonal_difference(&regular, s.period, s.d),
+            None => regular,
+        }
+    }
+
+    // Observations consumed by differencing
+    fn lost_observations(&self) -> usize {
+        self.d + self.seasonal.map_or(0, |s| s.d * s.period)
+    }
+
+    // Shortest series this spec can be fitted on with `n_exog` regressors
+    pub(crate) fn min_observations(&self, n_exog: usize) -> usize {
+        let (sp, sq, period) = self.seasonal.map_or((0, 0, 0), |s| (s.p, s.q, s.period));
+        let n_arma = self.p + self.q + sp + sq;
+        let max_lag = (self.p + sp * period).max(self.q + sq * period);
+        self.lost_observations() + max_lag + n_arma + n_exog + 2
+    }
+}
+
+// Only the most recent in-sample residuals are persisted, so a saved model
+// does not grow with the training set
+const PERSISTED_RESIDUALS: usize = 512;
+
+// Innovations appended by `update` are kept in a window of this many values
+// (or the fit-time count when larger), so a model updated for months keeps
+// bounded memory like the forecast state. Shared with the ETS models and
+// ensembles.
+pub(crate) const RESIDUAL_WINDOW: usize = PERSISTED_RESIDUALS;
+
+// Append to a residual buffer holding at most twice `window` values; the
+// oldest half is dropped at once so the cost stays amortized O(1)
+pub(crate) fn push_residual(residuals: &mut Vec<f64>, value: f64, window: usize) {
+    residuals.push(value);
+    if residuals.len() >= 2 * window {
+        residuals.drain(..residuals.len() - window);
+    }
+}
+
+// Most recent `window` values of a residual buffer
+pub(crate) fn residual_window(residuals: &[f64], window: usize) -> &[f64] {
+    &residuals[residuals.len().saturating_sub(window)..]
+}
+
+fn serialize_residual_tail<S: Serializer>(
+    residuals: &[f64],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let start = residuals.len().saturating_sub(PERSISTED_RESIDUALS);
+    residuals[start..].serialize(serializer)
 }
 
 // ARIMA model struct
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ArimaModel {
     pub ar_coeffs: Vec<f64>,
     pub ma_coeffs: Vec<f64>,
+    pub seasonal_ar_coeffs: Vec<f64>,
+    pub seasonal_ma_coeffs: Vec<f64>,
     pub d_order: usize,
+    pub exog_coeffs: Vec<f64>,
+    seasonal: Option<SeasonalOrder>,
+    full_ar: Vec<f64>,
+    full_ma: Vec<f64>,
     mean: f64,
-    std_dev: f64,
-    original_series: Vec<f64>,
-    differenced_series: Vec<f64>,
+    include_mean: bool,
+    sigma2: f64,
+    log_likelihood: f64,
+    last_exog: Vec<f64>,
+    origin: ForecastOrigin,
+    rls: Option<RlsState>,
+    nobs: usize, // Observations in the likelihood, fixed at fit time
+    #[serde(skip)]
+    updates: usize, // Observations appended by `update` since the fit; not saved
+    #[serde(serialize_with = "serialize_residual_tail")]
     residuals: Vec<f64>,
 }
 
 impl ArimaModel {
-    // Fit ARIMA(p,d,q) model
-    pub fn fit(
-        data: &[Data],
+    // Fit ARIMA(p,d,q) model on a column of the dataset
+    pub fn fit(data: &Dataset, field: &str, p: usize, d: usize, q: usize) -> Result<Self> {
+        let series = extract_series(data, field)?;
+        Self::fit_series(&series, p, d, q)
+    }
+
+    // Fit ARIMA(p,d,q) on a plain series
+    pub fn fit_series(series: &[f64], p: usize, d: usize, q: usize) -> Result<Self> {
+        Self::fit_spec(series, &ArimaSpec::new(p, d, q))
+    }
+
+    // Fit SARIMA(p,d,q)(P,D,Q)s on a plain series
+    pub fn fit_spec(series: &[f64], spec: &ArimaSpec) -> Result<Self> {
+        Self::fit_series_with_exog(series, &[], spec)
+    }
+
+    // Regression with SARIMA errors on dataset columns: `field` is explained
+    // by the `exog_fields` columns plus a SARIMA noise process
+    pub fn fit_with_exog(
+        data: &Dataset,
         field: &str,
-        p: usize,
-        d: usize,
-        q: usize,
-    ) -> Result<Self, Box<dyn Error>> {
-        let series = extract_series(data, field);
+        exog_fields: &[&str],
+        spec: &ArimaSpec,
+    ) -> Result<Self> {
+        let series = extract_series(data, field)?;
+        let exog = exog_fields
+            .iter()
+            .map(|f| extract_series(data, f))
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::fit_series_with_exog(&series, &exog, spec)
+    }
+
+    // Fit y_t = beta' x_t + n_t with n_t ~ SARIMA(p,d,q)(P,D,Q)s by
+    // conditional sum of squares followed by exact Gaussian maximum
+    // likelihood. `exog` holds one column per regressor. As in most
+    // statistical packages the mean is only estimated when the series is not
+    // differenced.
+    pub fn fit_series_with_exog(
+        series: &[f64],
+        exog: &[Vec<f64>],
+        spec: &ArimaSpec,
+    ) -> Result<Self> {
+        let (sp, sq, period) = spec.seasonal.map_or((0, 0, 0), |s| (s.p, s.q, s.period));
+        if spec.seasonal.is_some() && period < 2 {
+            return Err(Error::Model("Seasonal period must be at least 2".into()));
+        }
 
         // Check if we have enough data
-        if series.len() <= p + d + q {
-            return Err("Not enough data points for the specified ARIMA model".into());
+        let n_arma = spec.p + spec.q + sp + sq;
+        let max_lag = (spec.p + sp * period).max(spec.q + sq * period);
+        if series.len() < spec.min_observations(exog.len()) {
+            return Err(Error::Data(
+                "Not enough data points for the specified ARIMA model".into(),
+            ));
+        }
+        if max_lag + 1 > MAX_STATE_DIMENSION {
+            return Err(Error::Model(format!(
+                "Seasonal lags up to {} are too long; resample the series to a coarser interval",
+                max_lag
+            )));
+        }
+        if exog.iter().any(|x| x.len() != series.len()) {
+            return Err(Error::Data(
+                "Exogenous regressors must have the same length as the series".into(),
+            ));
         }
 
-        // Apply differencing
-        let differenced = difference(&series, d);
+        // Missing values from lenient loading must be filled beforehand
+        if series
+            .iter()
+            .chain(exog.iter().flatten())
+            .any(|v| v.is_nan())
+        {
+            return Err(Error::Data(
+                "Series contains missing values; fill gaps before fitting".into(),
+            ));
+        }
 
-        // Calculate mean and standard deviation of differenced series
-        let mean = differenced.iter().sum::<f64>() / differenced.len() as f64;
-        let variance =
-            differenced.iter().map(|&x| (x - mean).powi(2)).sum::<f64>() / differenced.len() as f64;
-        let std_dev = variance.sqrt();
+        // Apply the same differencing to the series and the regressors
+        let differenced = spec.differenced(series);
+        let differenced_exog: Vec<Vec<f64>> = exog.iter().map(|x| spec.differenced(x)).collect();
 
-        // Fit AR model to get coefficients
-        let ar_coeffs = fit_ar(&differenced, p);
+        let include_mean = spec.lost_observations() == 0;
 
-        // Calculate residuals
-        let mut residuals = Vec::with_capacity(differenced.len() - p);
-        for i in p..differenced.len() {
-            let mut predicted = mean;
-            for j in 0..p {
-                predicted += ar_coeffs[j] * (differenced[i - j - 1] - mean);
-            }
-            residuals.push(differenced[i] - predicted);
+        // Starting values for the mean and beta by OLS on the differenced data
+        let rows = differenced.len();
+        let cols = include_mean as usize + exog.len();
+        let mut regression_start: Vec<f64> = Vec::new();
+        if cols > 0 {
+            let x = DMatrix::from_fn(rows, cols, |r, c| {
+                if include_mean && c == 0 {
+                    1.0
+                } else {
+                    differenced_exog[c - include_mean as usize][r]
+                }
+            });
+            let y = DVector::from_column_slice(&differenced);
+            let (beta, _) = ols(&x, &y)
+                .ok_or_else(|| Error::Data("Exogenous regressors are collinear".into()))?;
+            regression_start.extend(beta.iter());
+        }
+
+        // Noise after removing the regression part
+        let noise = |beta: &[f64]| -> Vec<f64> {
+            differenced
+                .iter()
+                .enumerate()
+                .map(|(t, w)| {
+                    w - beta
+                        .iter()
+                        .zip(&differenced_exog)
+                        .map(|(b, x)| b * x[t])
+                        .sum::<f64>()
+                })
+                .collect()
+        };
+
+        let mut start = vec![0.0; n_arma];
+        start.extend(regression_start);
+        let options = NelderMeadOptions::default();
+
+        // Step 1: conditional sum of squares gives starting values
+        let css = nelder_mead(
+            |params| {
+                let decoded = ArmaParams::unpack(params, spec, include_mean);
+                let (ar, ma) = decoded.expanded(spec);
+                let residuals = css_residuals(&noise(&decoded.exog), &ar, &ma, decoded.mean);
+                let ss = residuals.iter().map(|e| e * e).sum::<f64>();
+                0.5 * residuals.len() as f64 * (ss / residuals.len() as f64).ln()
+            },
+            &start,
+            &options,
+        );
+        if !css.converged {
+            return Err(Error::Optimization(format!(
+                "Conditional sum of squares did not converge in {} iterations",
+                css.iterations
+            )));
+        }
+
+        // Step 2: exact likelihood through the Kalman filter
+        let mle = nelder_mead(
+            |params| {
+                let decoded = ArmaParams::unpack(params, spec, include_mean);
+                let (ar, ma) = decoded.expanded(spec);
+                -kalman_likelihood(&noise(&decoded.exog), &ar, &ma, decoded.mean).log_likelihood
+            },
+            &css.x,
+            &options,
+        );
+        if !mle.value.is_finite() {
+            return Err(Error::Optimization(
+                "Likelihood optimization failed for the specified ARIMA model".into(),
+            ));
+        }
+        if !mle.converged {
+            return Err(Error::Optimization(format!(
+                "Likelihood optimization did not converge in {} iterations",
+                mle.iterations
+            )));
         }
 
-        // Fit MA model to residuals
-        let ma_coeffs = fit_ma(&residuals, q);
+        let decoded = ArmaParams::unpack(&mle.x, spec, include_mean);
+        let (full_ar, full_ma) = decoded.expanded(spec);
+        let differenced_noise = noise(&decoded.exog);
+        let output = kalman_likelihood(&differenced_noise, &full_ar, &full_ma, decoded.mean);
+
+        // The forecast integrates the noise process n_t = y_t - beta' x_t
+        let original_noise: Vec<f64> = (0..series.len())
+            .map(|t| {
+                series[t]
+                    - decoded
+                        .exog
+                        .iter()
+                        .zip(exog)
+                        .map(|(b, x)| b * x[t])
+                        .sum::<f64>()
+            })
+            .collect();
 
         Ok(ArimaModel {
-            ar_coeffs,
-            ma_coeffs,
-            d_order: d,
-            mean,
-            std_dev,
-            original_series: series,
-            differenced_series: differenced,
-            residuals,
+            ar_coeffs: decoded.ar,
+            ma_coeffs: decoded.ma,
+            seasonal_ar_coeffs: decoded.seasonal_ar,
+            seasonal_ma_coeffs: decoded.seasonal_ma,
+            d_order: spec.d,
+            exog_coeffs: decoded.exog,
+            seasonal: spec.seasonal,
+            mean: decoded.mean,
+            include_mean,
+            sigma2: output.sigma2,
+            log_likelihood: output.log_likelihood,
+            last_exog: exog.iter().map(|x| x[x.len() - 1]).collect(),
+            origin: ForecastOrigin::new(
+                integration_coeffs(spec.d, spec.seasonal),
+                &original_noise,
+                &differenced_noise,
+                &output.innovations,
+                full_ar.len(),
+                full_ma.len(),
+                decoded.mean,
+            ),
+            full_ar,
+            full_ma,
+            rls: None,
+            nobs: differenced_noise.len(),
+            updates: 0,
+            residuals: output.innovations,
         })
     }
 
-    // Forecast future values
-    pub fn forecast(&self, steps: usize) -> Vec<f64> {
-        let p = self.ar_coeffs.len();
-        let q = self.ma_coeffs.len();
+    // Consistency of the buffers with the model orders. The derived
+    // `Deserialize` accepts any lengths, so a corrupted or edited file is
+    // checked here before `update` or `forecast` can index out of bounds.
+    pub(crate) fn check_state(&self) -> Result<()> {
+        let (period, seasonal_d) = self.seasonal.map_or((0, 0), |s| (s.period, s.d));
+        if self.seasonal.is_some() && period < 2 {
+            return Err(Error::Serialization(
+                "Seasonal period must be at least 2".into(),
+            ));
+        }
+        if self.seasonal.is_none()
+            && !(self.seasonal_ar_coeffs.is_empty() && self.seasonal_ma_coeffs.is_empty())
+        {
+            return Err(Error::Serialization(
+                "Seasonal coefficients without a seasonal order".into(),
+            ));
+        }
 
-        // Create forecasted differenced series
-        let mut forecasted = Vec::with_capacity(steps);
-        let mut extended_residuals = self.residuals.clone();
+        let k = self.full_ar.len() + self.full_ma.len();
+        let checks = [
+            (
+                "expanded AR polynomial",
+                self.full_ar.len(),
+                self.ar_coeffs.len() + self.seasonal_ar_coeffs.len() * period,
+            ),
+            (
+                "expanded MA polynomial",
+                self.full_ma.len(),
+                self.ma_coeffs.len() + self.seasonal_ma_coeffs.len() * period,
+            ),
+            (
+                "differencing coefficients",
+                self.origin.delta.len(),
+                self.d_order + seasonal_d * period,
+            ),
+            (
+                "level buffer",
+                self.origin.levels.len(),
+                self.origin.delta.len(),
+            ),
+            ("AR buffer", self.origin.values.len(), self.full_ar.len()),
+            ("MA buffer", self.origin.residuals.len(), self.full_ma.len()),
+            (
+                "exogenous values",
+                self.last_exog.len(),
+                self.exog_coeffs.len(),
+            ),
+            (
+                "RLS covariance",
+                self.rls.as_ref().map_or(k * k, |rls| rls.covariance.len()),
+                k * k,
+            ),
+        ];
+        for (name, found, expected) in checks {
+            if found != expected {
+                return Err(Error::Serialization(format!(
+                    "Invalid model state: {} has length {}, expected {}",
+                    name, found, expected
+                )));
+            }
+        }
+        Ok(())
+    }
 
-        // Get most recent values for forecasting
-        let recent_diff_values: Vec<f64> = self
-            .differenced_series
-            .iter()
-            .rev()
-            .take(p)
-            .cloned()
-            .collect();
+    // Coefficients and current forecast state, for code generation
+    pub fn predictor_parts(&self) -> PredictorParts {
+        PredictorParts {
+            ar: self.full_ar.clone(),
+            ma: self.full_ma.clone(),
+            delta: self.origin.delta.clone(),
+            mean: self.mean,
+            levels: self.origin.levels.iter().copied().collect(),
+            values: self.origin.values.iter().copied().collect(),
+            residuals: self.origin.residuals.iter().copied().collect(),
+        }
+    }
 
-        // Forecast each step
-        for _ in 0..steps {
-            let mut forecast_value = self.mean;
+    // Orders the model was fitted with
+    pub fn spec(&self) -> ArimaSpec {
+        ArimaSpec {
+            p: self.ar_coeffs.len(),
+            d: self.d_order,
+            q: self.ma_coeffs.len(),
+            seasonal: self.seasonal,
+        }
+    }
 
-            // Add AR component
-            for j in 0..p {
-                if j < recent_diff_values.len() {
-                    forecast_value += self.ar_coeffs[j] * (recent_diff_values[j] - self.mean);
-                }
-            }
+    // Enable recursive least squares adaptation of the AR and MA
+    // coefficients during `update`. A forgetting factor below 1 discounts
+    // old observations (0.99 gives an effective memory of ~100 samples).
+    pub fn enable_rls(&mut self, forgetting_factor: f64) -> Result<()> {
+        if !(forgetting_factor > 0.0 && forgetting_factor <= 1.0) {
+            return Err(Error::Model("Forgetting factor must be in (0, 1]".into()));
+        }
+        if self.seasonal.is_some() {
+            return Err(Error::Model(
+                "RLS adaptation is only supported for non-seasonal models".into(),
+            ));
+        }
+
+        // Start with the confidence of the batch fit over the effective memory
+        let k = self.full_ar.len() + self.full_ma.len();
+        let memory = if forgetting_factor < 1.0 {
+            (1.0 / (1.0 - forgetting_factor)).min(self.nobs as f64)
+        } else {
+            self.nobs as f64
+        };
+        let scale = 1.0 / (self.sigma2.max(1e-12) * memory.max(1.0));
+        let mut covariance = vec![0.0; k * k];
+        for i in 0..k {
+            covariance[i * k + i] = scale;
+        }
+
+        self.rls = Some(RlsState {
+            forgetting: forgetting_factor,
+            covariance,
+        });
+        Ok(())
+    }
+
+    // Append a new observation and return the forecast for the next step.
+    // Only the bounded forecast state is updated, so the cost is O(p + q)
+    // (O((p + q)^2) with RLS enabled) regardless of the history length.
+    pub fn update(&mut self, observation: f64) -> Result<f64> {
+        let exog = self.last_exog.clone();
+        self.update_with_exog(observation, &exog)
+    }
+
+    // Same as `update` for models with regressors: `exog` holds the current
+    // value of each regressor
+    pub fn update_with_exog(&mut self, observation: f64, exog: &[f64]) -> Result<f64> {
+        if !observation.is_finite() {
+            return Err(Error::Data("Observation must be a finite number".into()));
+        }
+        if exog.len() != self.exog_coeffs.len() {
+            return Err(Error::Data(format!(
+                "Expected {} exogenous values, got {}",
+                self.exog_coeffs.len(),
+                exog.len()
+            )));
+        }
+
+        // Noise level, its differenced value and the one-step error
+        let regression: f64 = self.exog_coeffs.iter().zip(exog).map(|(b, x)| b * x).sum();
+        let level = observation - regression;
+        let value = level - self.origin.integrate(0.0);
+        let predicted = self
+            .origin
+            .predict_value(&self.full_ar, &self.full_ma, self.mean);
+        let residual = value - predicted;
+
+        if let Some(rls) = self.rls.as_mut() {
+            // Regressors of the ARMA equation at this step
+            let z: Vec<f64> = self
+                .origin
+                .values
+                .iter()
+                .rev()
+                .map(|w| w - self.mean)
+                .chain(self.origin.residuals.iter().rev().copied())
+                .collect();
+            let k = z.len();
+            let pz: Vec<f64> = (0..k)
+                .map(|i| (0..k).map(|j| rls.covariance[i * k + j] * z[j]).sum())
+                .collect();
+            let denominator = rls.forgetting + z.iter().zip(&pz).map(|(a, b)| a * b).sum::<f64>();
+            let gain: Vec<f64> = pz.iter().map(|v| v / denominator).collect();
 
-            // Add MA component
-            for j in 0..q {
-                if j < extended_residuals.len() {
-                    forecast_value +=
-                        self.ma_coeffs[j] * extended_residuals[extended_residuals.len() - 1 - j];
+            for (coeff, g) in self
+                .full_ar
+                .iter_mut()
+                .chain(self.full_ma.iter_mut())
+                .zip(&gain)
+            {
+                *coeff += g * residual;
+            }
+            let forgetting = rls.forgetting;
+            for (row, g) in rls.covariance.chunks_mut(k).zip(&gain) {
+                for (c, p) in row.iter_mut().zip(&pz) {
+                    *c = (*c - g * p) / forgetting;
                 }
             }
+            self.ar_coeffs.clone_from(&self.full_ar);
+            self.ma_coeffs.clone_from(&self.full_ma);
+        }
 
-            // Add new forecasted value and assume zero for new residual
-            forecasted.push(forecast_value);
-            extended_residuals.push(0.0);
+        self.origin.advance(level, value, residual);
+        self.last_exog = exog.to_vec();
+        // The likelihood and the information criteria keep their fit-time
+        // values; only the residuals grow with the new innovation
+        let capacity = self.residual_capacity();
+        push_residual(&mut self.residuals, residual, capacity);
+        self.updates += 1;
+
+        let next = self
+            .origin
+            .predict_value(&self.full_ar, &self.full_ma, self.mean);
+        Ok(self.origin.integrate(next) + regression)
+    }
+
+    // Innovation variance estimated by maximum likelihood
+    pub fn sigma2(&self) -> f64 {
+        self.sigma2
+    }
+
+    // In-sample one-step prediction errors from the Kalman filter, followed
+    // by the innovations of `update` (the last `RESIDUAL_WINDOW` values, or
+    // the fit-time count when larger)
+    pub fn residuals(&self) -> &[f64] {
+        residual_window(&self.residuals, self.residual_capacity())
+    }
+
+    fn residual_capacity(&self) -> usize {
+        self.nobs.max(RESIDUAL_WINDOW)
+    }
+
+    // Exact Gaussian log-likelihood of the differenced series
+    pub fn log_likelihood(&self) -> f64 {
+        self.log_likelihood
+    }
+
+    // Forecast future values. Models with exogenous regressors hold their
+    // last observed values constant; use `forecast_with_exog` for known
+    // future values such as a planned heater schedule.
+    pub fn forecast(&self, steps: usize) -> Vec<f64> {
+        let regression: f64 = self
+            .exog_coeffs
+            .iter()
+            .zip(&self.last_exog)
+            .map(|(b, x)| b * x)
+            .sum();
+        self.forecast_noise(steps)
+            .into_iter()
+            .map(|n| n + regression)
+            .collect()
+    }
+
+    // Forecast with future values of the regressors, one column per
+    // regressor in the same order used for fitting
+    pub fn forecast_with_exog(&self, future_exog: &[Vec<f64>]) -> Result<Vec<f64>> {
+        if future_exog.len() != self.exog_coeffs.len() {
+            return Err(Error::Data(format!(
+                "Expected {} exogenous columns, got {}",
+                self.exog_coeffs.len(),
+                future_exog.len()
+            )));
+        }
+        let steps = future_exog.first().map_or(0, |x| x.len());
+        if future_exog.iter().any(|x| x.len() != steps) {
+            return Err(Error::Data(
+                "Future exogenous columns must have the same length".into(),
+            ));
         }
 
-        // Invert differencing to get original scale forecasts
-        let mut result = forecasted;
+        Ok(self
+            .forecast_noise(steps)
+            .into_iter()
+            .enumerate()
+            .map(|(h, n)| {
+                n + self
+                    .exog_coeffs
+                    .iter()
+                    .zip(future_exog)
+                    .map(|(b, x)| b * x[h])
+                    .sum::<f64>()
+            })
+            .collect())
+    }
+
+    // Forecast of the SARIMA noise process on the original scale
+    fn forecast_noise(&self, steps: usize) -> Vec<f64> {
+        self.origin
+            .forecast(&self.full_ar, &self.full_ma, self.mean, steps)
+    }
 
-        // Inverse differencing (simplified approach)
-        for _ in 0..self.d_order {
-            let last_original = self.original_series.last().unwrap_or(&0.0);
-            for i in 0..result.len() {
-                if i == 0 {
-                    result[i] += last_original;
-                } else {
-                    result[i] += result[i - 1];
-                }
+    // MA(infinity) weights of the full model, differencing included:
+    // psi_0 = 1, psi_j = theta_j + sum phi*_k psi_{j-k}
+    pub fn psi_weights(&self, n: usize) -> Vec<f64> {
+        // AR polynomial times the differencing polynomial, in 1 - sum c_k B^k form
+        let delta = integration_coeffs(self.d_order, self.seasonal);
+        let mut combined = vec![0.0; self.full_ar.len() + delta.len()];
+        for (i, a) in self.full_ar.iter().enumerate() {
+            combined[i] += a;
+        }
+        for (k, d) in delta.iter().enumerate() {
+            combined[k] += d;
+            for (i, a) in self.full_ar.iter().enumerate() {
+                combined[i + k + 1] -= a * d;
+            }
+        }
+
+        let mut psi = Vec::with_capacity(n);
+        for j in 0..n {
+            if j == 0 {
+                psi.push(1.0);
+                continue;
             }
+            let mut value = self.full_ma.get(j - 1).copied().unwrap_or(0.0);
+            for (k, c) in combined.iter().enumerate().take(j) {
+                value += c * psi[j - k - 1];
+            }
+            psi.push(value);
         }
+        psi
+    }
+
+    // Forecast with standard errors and Gaussian prediction intervals at the
+    // given confidence levels (e.g. &[0.8, 0.95])
+    pub fn forecast_with_intervals(
+        &self,
+        steps: usize,
+        levels: &[f64],
+    ) -> Result<IntervalForecast> {
+        let mean = self.forecast(steps);
+
+        // Var(e_h) = sigma^2 * sum_{j<h} psi_j^2
+        let mut cumulative = 0.0;
+        let std_error: Vec<f64> = self
+            .psi_weights(steps)
+            .iter()
+            .map(|psi| {
+                cumulative += psi * psi;
+                (self.sigma2 * cumulative).sqrt()
+            })
+            .collect();
 
-        result
+        IntervalForecast::gaussian(mean, std_error, levels)
     }
 
     // Calculate AIC (Akaike Information Criterion) for model selection
     pub fn aic(&self) -> f64 {
-        let n = self.residuals.len() as f64;
-        let k = (self.ar_coeffs.len() + self.ma_coeffs.len() + 1) as f64; // +1 for variance
+        -2.0 * self.log_likelihood + 2.0 * self.n_params() as f64
+    }
+
+    // AIC with small-sample correction
+    pub fn aicc(&self) -> f64 {
+        let k = self.n_params() as f64;
+        let n = self.nobs() as f64;
+        if n - k - 1.0 <= 0.0 {
+            return f64::INFINITY;
+        }
+        self.aic() + 2.0 * k * (k + 1.0) / (n - k - 1.0)
+    }
+
+    // Bayesian (Schwarz) information criterion
+    pub fn bic(&self) -> f64 {
+        -2.0 * self.log_likelihood + self.n_params() as f64 * (self.nobs() as f64).ln()
+    }
 
-        // Sum of squared residuals
-        let rss = self.residuals.iter().map(|r| r.powi(2)).sum::<f64>();
+    // Number of observations used by the likelihood (after differencing)
+    pub fn nobs(&self) -> usize {
+        self.nobs
+    }
 
-        // Log-likelihood (simplified)
-        let log_likelihood = -0.5 * n * (1.0 + (2.0 * std::f64::consts::PI).ln() + (rss / n).ln());
+    // Observations appended with `update` since the model was fitted
+    pub fn updates(&self) -> usize {
+        self.updates
+    }
 
-        // AIC formula
-        -2.0 * log_likelihood + 2.0 * k
+    // ARMA coefficients, the mean when estimated, and the variance
+    pub(crate) fn n_params(&self) -> usize {
+        self.ar_coeffs.len()
+            + self.ma_coeffs.len()
+            + self.seasonal_ar_coeffs.len()
+            + self.seasonal_ma_coeffs.len()
+            + self.include_mean as usize
+            + self.exog_coeffs.len()
+            + 1
     }
 }
 
 // Example usage function
-pub fn analyze_time_series(filepath: &str) -> Result<(), Box<dyn Error>> {
+pub fn analyze_time_series(filepath: &str) -> Result<()> {
     // Read data
     let data = read_csv(filepath)?;
 
@@ -231,6 +1159,7 @@ pub fn analyze_time_series(filepath: &str) -> Result<(), Box<dyn Error>> {
     println!("AR coefficients: {:?}", arima_model.ar_coeffs);
     println!("MA coefficients: {:?}", arima_model.ma_coeffs);
     println!("Mean of differenced series: {}", arima_model.mean);
+    println!("Innovation variance: {}", arima_model.sigma2());
     println!("AIC: {}", arima_model.aic());
 
     // Generate forecast
@@ -239,3 +1168,197 @@ pub fn analyze_time_series(filepath: &str) -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[test]
+fn test_fit_recovers_arma_coefficients() {
+    use super::test_util::Lcg;
+
+    // ARMA(1,1) with phi = 0.6, theta = 0.3 and mean 20
+    let mut rng = Lcg::new(7);
+    let mut series = Vec::with_capacity(3000);
+    let (mut last, mut last_e) = (0.0, 0.0);
+    for _ in 0..3000 {
+        let e = rng.gaussian();
+        last = 0.6 * last + e + 0.3 * last_e;
+        last_e = e;
+        series.push(20.0 + last);
+    }
+
+    let model = ArimaModel::fit_series(&series, 1, 0, 1).unwrap();
+    assert!((model.ar_coeffs[0] - 0.6).abs() < 0.05);
+    assert!((model.ma_coeffs[0] - 0.3).abs() < 0.05);
+    assert!((model.mean - 20.0).abs() < 0.2);
+    assert!((model.sigma2() - 1.0).abs() < 0.1);
+}
+
+#[test]
+fn test_sarima_forecast_repeats_seasonal_pattern() {
+    use super::test_util::Lcg;
+
+    // Ciclo diario de 24 puntos con tendencia lenta y algo de ruido
+    let mut rng = Lcg::new(3);
+    let series: Vec<f64> = (0..24 * 12)
+        .map(|t| {
+            let noise = rng.noise();
+            let hour = (t % 24) as f64;
+            25.0 + 0.01 * t as f64 + 3.0 * (hour * std::f64::consts::PI / 12.0).sin() + 0.1 * noise
+        })
+        .collect();
+
+    let spec = ArimaSpec::new(1, 0, 0).with_seasonal(SeasonalOrder::new(0, 1, 1, 24));
+    let model = ArimaModel::fit_spec(&series, &spec).unwrap();
+    assert_eq!(model.spec(), spec);
+    assert_eq!(model.seasonal_ma_coeffs.len(), 1);
+
+    let forecast = model.forecast(24);
+    let n = series.len();
+    for h in 0..24 {
+        // Un dia despues: mismo patron desplazado por la tendencia
+        let expected = series[n - 24 + h] + 0.24;
+        assert!(
+            (forecast[h] - expected).abs() < 0.3,
+            "h={} {} vs {}",
+            h,
+            forecast[h],
+            expected
+        );
+    }
+}
+
+#[test]
+fn test_random_walk_intervals_grow_with_sqrt_horizon() {
+    use super::test_util::Lcg;
+    let mut rng = Lcg::new(11);
+    let mut level = 0.0;
+    let series: Vec<f64> = (0..400)
+        .map(|_| {
+            level += rng.noise();
+            level
+        })
+        .collect();
+
+    let model = ArimaModel::fit_series(&series, 0, 1, 0).unwrap();
+    let result = model.forecast_with_intervals(9, &[0.8, 0.95]).unwrap();
+    assert!((result.std_error[8] / result.std_error[0] - 3.0).abs() < 1e-12);
+
+    let wide = result.interval(0.95).unwrap();
+    let narrow = result.interval(0.8).unwrap();
+    assert!(wide.upper[0] > narrow.upper[0] && narrow.upper[0] > result.mean[0]);
+    assert!((wide.upper[0] - result.mean[0] - 1.959964 * result.std_error[0]).abs() < 1e-5);
+    assert!(model.forecast_with_intervals(3, &[1.5]).is_err());
+}
+
+#[test]
+fn test_arimax_recovers_regression_coefficient() {
+    use super::test_util::Lcg;
+
+    // Humedad = 90 - 0.8 * temperatura + ruido AR(1)
+    let mut rng = Lcg::new(21);
+    let temperature: Vec<f64> = (0..500)
+        .map(|t| 25.0 + 3.0 * (t as f64 / 40.0).sin() + rng.noise())
+        .collect();
+    let mut noise = 0.0;
+    let humidity: Vec<f64> = temperature
+        .iter()
+        .map(|x| {
+            noise = 0.5 * noise + 0.2 * rng.noise();
+            90.0 - 0.8 * x + noise
+        })
+        .collect();
+
+    let spec = ArimaSpec::new(1, 0, 0);
+    let model = ArimaModel::fit_series_with_exog(&humidity, &[temperature], &spec).unwrap();
+    assert!((model.exog_coeffs[0] + 0.8).abs() < 0.02);
+    assert!((model.ar_coeffs[0] - 0.5).abs() < 0.1);
+
+    // Con la temperatura futura conocida el pronostico sigue la regresion
+    let future = vec![vec![20.0, 30.0]];
+    let forecast = model.forecast_with_exog(&future).unwrap();
+    assert!((forecast[0] - forecast[1] - 8.0).abs() < 0.3);
+    assert!(model.forecast_with_exog(&[]).is_err());
+}
+
+#[test]
+fn test_forecast_origin_reference_values() {
+    // AR(1) con phi = 0.5 y ultimo valor diferenciado 2: w = 1, 0.5, 0.25
+    let ar = [0.5];
+    let d0 = ForecastOrigin::new(integration_coeffs(0, None), &[2.0], &[2.0], &[], 1, 0, 0.0);
+    assert_eq!(d0.forecast(&ar, &[], 0.0, 3), vec![1.0, 0.5, 0.25]);
+
+    // d = 1, ultimo nivel 10: y = 11, 11.5, 11.75
+    let d1 = ForecastOrigin::new(integration_coeffs(1, None), &[10.0], &[2.0], &[], 1, 0, 0.0);
+    assert_eq!(d1.forecast(&ar, &[], 0.0, 3), vec![11.0, 11.5, 11.75]);
+
+    // d = 2, ultimos niveles 8 y 10: y = w + 2 y_{t-1} - y_{t-2}
+    let d2 = ForecastOrigin::new(
+        integration_coeffs(2, None),
+        &[8.0, 10.0],
+        &[2.0],
+        &[],
+        1,
+        0,
+        0.0,
+    );
+    assert_eq!(d2.forecast(&ar, &[], 0.0, 3), vec![13.0, 16.5, 20.25]);
+
+    // MA(1) con media 5: solo el primer paso usa el ultimo residuo
+    let ma = ForecastOrigin::new(integration_coeffs(0, None), &[], &[], &[0.4], 0, 1, 5.0);
+    assert_eq!(ma.forecast(&[], &[0.5], 5.0, 3), vec![5.2, 5.0, 5.0]);
+}
+
+#[test]
+fn test_arima_020_extrapolates_last_slope() {
+    use super::test_util::Lcg;
+    let mut rng = Lcg::new(5);
+    let series: Vec<f64> = (0..200)
+        .map(|t| {
+            let noise = rng.noise();
+            0.01 * (t * t) as f64 + noise
+        })
+        .collect();
+
+    // Sin terminos ARMA ni media, d = 2 continua la ultima pendiente
+    let model = ArimaModel::fit_series(&series, 0, 2, 0).unwrap();
+    let n = series.len();
+    let slope = series[n - 1] - series[n - 2];
+    let forecast = model.forecast(3);
+    for (h, value) in forecast.iter().enumerate() {
+        let expected = series[n - 1] + (h + 1) as f64 * slope;
+        assert!((value - expected).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn test_update_matches_refit_free_recursion() {
+    use super::test_util::Lcg;
+    let mut rng = Lcg::new(17);
+    let mut value = 0.0;
+    let series: Vec<f64> = (0..400)
+        .map(|_| {
+            value = 0.6 * value + rng.noise();
+            10.0 + value
+        })
+        .collect();
+
+    let mut model = ArimaModel::fit_series(&series[..300], 1, 0, 0).unwrap();
+    for &y in &series[300..] {
+        let next = model.update(y).unwrap();
+        let expected = model.mean + model.ar_coeffs[0] * (y - model.mean);
+        assert!((next - expected).abs() < 1e-12);
+    }
+    let next = model.update(series[399]).unwrap();
+    assert!((model.forecast(1)[0] - next).abs() < 1e-12);
+
+    // RLS sigue un cambio de dinamica: phi pasa de 0.6 a -0.5
+    model.enable_rls(0.98).unwrap();
+    for _ in 0..1500 {
+        value = -0.5 * value + rng.noise();
+        model.update(10.0 + value).unwrap();
+    }
+    assert!((model.ar_coeffs[0] + 0.5).abs() < 0.15);
+
+    // 1601 actualizaciones: los residuos quedan en una ventana acotada
+    assert_eq!(model.residuals().len(), RESIDUAL_WINDOW);
+    assert!(model.residuals.len() < 2 * RESIDUAL_WINDOW);
+    assert_eq!(model.nobs(), 300);
+}
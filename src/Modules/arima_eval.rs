@@ -1,4 +1,4 @@
-use super::data_struct::Data;
+use super::error::{Error, Result};
 
 /// Calcula los valores ponderados entre valores predichos y actuales
 ///
@@ -8,23 +8,31 @@ use super::data_struct::Data;
 /// - weights: Pesos opcionales (default si no es modificado)
 ///
 /// Returns:
-/// - El valor ponderado y metricas de analisis
+/// - El valor ponderado y metricas de analisis, o `Error::Evaluation` si los
+///   largos no coinciden, no hay valores o los pesos suman cero
 pub fn calculate_weighted_comparison(
     actual: &[f64],
     predicted: &[f64],
     weights: Option<&[f64]>,
-) -> WeightedComparisonResult {
+) -> Result<WeightedComparisonResult> {
     if actual.len() != predicted.len() {
-        panic!("Actual and predicted values must have the same length");
+        return Err(Error::Evaluation(
+            "Actual and predicted values must have the same length".into(),
+        ));
     }
 
     let n = actual.len();
+    if n == 0 {
+        return Err(Error::Evaluation("No values to compare".into()));
+    }
 
     // Use provided weights or default to equal weights
     let weights = match weights {
         Some(w) => {
             if w.len() != n {
-                panic!("Weights must have the same length as values");
+                return Err(Error::Evaluation(
+                    "Weights must have the same length as values".into(),
+                ));
             }
             w.to_vec()
         }
@@ -33,6 +41,11 @@ pub fn calculate_weighted_comparison(
 
     // Normalize weights to sum to 1.0 if they don't already
     let weight_sum: f64 = weights.iter().sum();
+    if !(weight_sum > 0.0 && weight_sum.is_finite()) {
+        return Err(Error::Evaluation(
+            "Weights must have a positive, finite sum".into(),
+        ));
+    }
     let normalized_weights: Vec<f64> = weights.iter().map(|w| w / weight_sum).collect();
 
     // Calculate weighted values
@@ -56,14 +69,14 @@ pub fn calculate_weighted_comparison(
     // Calculate weighted RMSE
     let weighted_rmse = weighted_squared_error.sqrt();
 
-    WeightedComparisonResult {
+    Ok(WeightedComparisonResult {
         weighted_values,
         original_weights: weights,
         normalized_weights,
         weighted_error,
         weighted_abs_error, // Weighted MAE
         weighted_rmse,
-    }
+    })
 }
 
 /// Structure to hold the results of weighted comparison
@@ -83,8 +96,19 @@ pub struct WeightedComparisonResult {
 /// -Lineal
 /// -Exponencial
 /// -Distancia Inversa
-pub fn generate_weights(len: usize, strategy: WeightingStrategy) -> Vec<f64> {
-    match strategy {
+///
+/// Con `len == 0` devuelve un vector vacio.
+pub fn generate_weights(len: usize, strategy: WeightingStrategy) -> Result<Vec<f64>> {
+    if len == 0 {
+        return match strategy {
+            WeightingStrategy::InverseDistance(distances) if !distances.is_empty() => Err(
+                Error::Evaluation("Distances must have the same length as values".into()),
+            ),
+            _ => Ok(Vec::new()),
+        };
+    }
+
+    let weights = match strategy {
         WeightingStrategy::Equal => vec![1.0 / len as f64; len],
 
         WeightingStrategy::Linear => {
@@ -94,6 +118,12 @@ pub fn generate_weights(len: usize, strategy: WeightingStrategy) -> Vec<f64> {
         }
 
         WeightingStrategy::Exponential(alpha) => {
+            if !(0.0..1.0).contains(&alpha) {
+                return Err(Error::Evaluation(
+                    "Exponential decay factor must be in [0, 1)".into(),
+                ));
+            }
+
             // Exponential weights with decay factor alpha
             let mut weights = Vec::with_capacity(len);
             let mut sum = 0.0;
@@ -110,7 +140,14 @@ pub fn generate_weights(len: usize, strategy: WeightingStrategy) -> Vec<f64> {
 
         WeightingStrategy::InverseDistance(distances) => {
             if distances.len() != len {
-                panic!("Distances must have the same length as values");
+                return Err(Error::Evaluation(
+                    "Distances must have the same length as values".into(),
+                ));
+            }
+            if distances.iter().any(|d| !d.is_finite() || *d < 0.0) {
+                return Err(Error::Evaluation(
+                    "Distances must be finite and non-negative".into(),
+                ));
             }
 
             let weights: Vec<f64> = distances
@@ -121,14 +158,33 @@ pub fn generate_weights(len: usize, strategy: WeightingStrategy) -> Vec<f64> {
             let sum: f64 = weights.iter().sum();
             weights.iter().map(|w| w / sum).collect()
         }
-    }
+    };
+    Ok(weights)
 }
 
 /// Enum para opciones de peso
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum WeightingStrategy {
     Equal,                     // Todos los pesos son iguales (se ve naranja por el todo)
     Linear,                    // Pesos incrementales lineales
     Exponential(f64),          // Pesos exponenciales con factor decayente
     InverseDistance(Vec<f64>), // Pesos calculados en la distancia inversa
 }
+
+#[test]
+fn test_invalid_inputs_return_errors() {
+    let result = calculate_weighted_comparison(&[1.0, 2.0], &[1.0], None);
+    assert!(matches!(result, Err(Error::Evaluation(_))));
+    assert!(calculate_weighted_comparison(&[], &[], None).is_err());
+    assert!(calculate_weighted_comparison(&[1.0], &[2.0], Some(&[0.0])).is_err());
+
+    assert_eq!(
+        generate_weights(0, WeightingStrategy::Linear).unwrap(),
+        Vec::<f64>::new()
+    );
+    assert!(generate_weights(3, WeightingStrategy::InverseDistance(vec![1.0])).is_err());
+
+    let weights = generate_weights(4, WeightingStrategy::Linear).unwrap();
+    let result = calculate_weighted_comparison(&[1.0; 4], &[0.0; 4], Some(&weights)).unwrap();
+    assert!((result.weighted_rmse - 1.0).abs() < 1e-12);
+}
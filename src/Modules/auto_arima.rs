@@ -0,0 +1,309 @@
+use super::arima::{ArimaModel, ArimaSpec};
+use super::error::{Error, Result};
+use super::forecaster::Forecaster;
+use super::stats::{adf, kpss};
+use std::collections::HashMap;
+
+/// Criterio de informacion que se minimiza
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InformationCriterion {
+    Aic,
+    Aicc,
+    Bic,
+}
+
+impl InformationCriterion {
+    pub fn score<M: Forecaster + ?Sized>(&self, model: &M) -> f64 {
+        match self {
+            InformationCriterion::Aic => model.aic(),
+            InformationCriterion::Aicc => model.aicc(),
+            InformationCriterion::Bic => model.bic(),
+        }
+    }
+}
+
+/// Como se recorre el espacio de ordenes (p, q)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SearchStrategy {
+    Stepwise,   // Busqueda local de Hyndman-Khandakar, pocos ajustes
+    Exhaustive, // Todas las combinaciones hasta max_p y max_q
+}
+
+/// Prueba usada para decidir el orden de diferenciacion
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnitRootTest {
+    Kpss, // Diferencia mientras se rechace la estacionariedad
+    Adf,  // Diferencia mientras no se rechace la raiz unitaria
+}
+
+/// Opciones de `auto_arima`
+#[derive(Debug, Clone)]
+pub struct AutoArimaOptions {
+    pub max_p: usize,
+    pub max_q: usize,
+    pub max_d: usize,
+    pub criterion: InformationCriterion,
+    pub strategy: SearchStrategy,
+    pub test: UnitRootTest,
+    pub alpha: f64,
+}
+
+impl Default for AutoArimaOptions {
+    fn default() -> Self {
+        AutoArimaOptions {
+            max_p: 5,
+            max_q: 5,
+            max_d: 2,
+            criterion: InformationCriterion::Aicc,
+            strategy: SearchStrategy::Stepwise,
+            test: UnitRootTest::Kpss,
+            alpha: 0.05,
+        }
+    }
+}
+
+/// Una fila de la tabla de candidatos evaluados
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub p: usize,
+    pub d: usize,
+    pub q: usize,
+    pub score: f64, // Valor del criterio elegido
+}
+
+/// Mejor modelo y tabla ordenada de todos los candidatos ajustados
+pub struct AutoArimaResult {
+    pub model: ArimaModel,
+    pub candidates: Vec<Candidate>,
+}
+
+/// Elige el orden de diferenciacion con pruebas de raiz unitaria sucesivas
+pub fn select_d(series: &[f64], test: UnitRootTest, alpha: f64, max_d: usize) -> Result<usize> {
+    let mut current = series.to_vec();
+    for d in 0..max_d {
+        let needs_diff = match test {
+            UnitRootTest::Kpss => kpss(&current, None)?.rejects_at(alpha),
+            UnitRootTest::Adf => !adf(&current, None)?.rejects_at(alpha),
+        };
+        if !needs_diff {
+            return Ok(d);
+        }
+        current = current.windows(2).map(|w| w[1] - w[0]).collect();
+    }
+    Ok(max_d)
+}
+
+/// Selecciona automaticamente un ARIMA(p, d, q)
+///
+/// Parameters:
+/// - series: la serie a modelar (sin valores faltantes)
+/// - options: limites de busqueda, criterio y prueba para `d`
+///
+/// Returns:
+/// - El mejor modelo segun el criterio y la tabla de candidatos del mejor al peor
+pub fn auto_arima(series: &[f64], options: &AutoArimaOptions) -> Result<AutoArimaResult> {
+    let d = select_d(series, options.test, options.alpha, options.max_d)?;
+
+    let mut search = Search {
+        series,
+        d,
+        options,
+        scores: HashMap::new(),
+        best: None,
+        failure: None,
+    };
+
+    match options.strategy {
+        SearchStrategy::Exhaustive => {
+            for p in 0..=options.max_p {
+                for q in 0..=options.max_q {
+                    search.try_order(p, q)?;
+                }
+            }
+        }
+        SearchStrategy::Stepwise => {
+            for (p, q) in [(2, 2), (0, 0), (1, 0), (0, 1)] {
+                search.try_order(p.min(options.max_p), q.min(options.max_q))?;
+            }
+
+            // Se mueve al vecino que mejore el criterio hasta que ninguno lo haga
+            let mut improved = true;
+            while improved {
+                improved = false;
+                let (bp, bq) = match &search.best {
+                    Some((p, q, _, _)) => (*p as i64, *q as i64),
+                    None => break,
+                };
+                let neighbours = [
+                    (bp - 1, bq),
+                    (bp + 1, bq),
+                    (bp, bq - 1),
+                    (bp, bq + 1),
+                    (bp - 1, bq - 1),
+                    (bp + 1, bq + 1),
+                    (bp - 1, bq + 1),
+                    (bp + 1, bq - 1),
+                ];
+                for (p, q) in neighbours {
+                    if p < 0 || q < 0 || p as usize > options.max_p || q as usize > options.max_q {
+                        continue;
+                    }
+                    if search.try_order(p as usize, q as usize)? {
+                        improved = true;
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    let mut candidates: Vec<Candidate> = search
+        .scores
+        .iter()
+        .map(|(&(p, q), &score)| Candidate { p, d, q, score })
+        .collect();
+    candidates.sort_by(|a, b| a.score.total_cmp(&b.score));
+
+    // Si ningun orden se pudo ajustar se devuelve la causa del ultimo fallo
+    let (_, _, _, model) = match search.best {
+        Some(best) => best,
+        None => {
+            return Err(search.failure.unwrap_or_else(|| {
+                Error::Model("No candidate ARIMA model could be fitted".into())
+            }));
+        }
+    };
+
+    Ok(AutoArimaResult { model, candidates })
+}
+
+// Estado de la busqueda: puntajes ya calculados y mejor modelo hasta ahora
+struct Search<'a> {
+    series: &'a [f64],
+    d: usize,
+    options: &'a AutoArimaOptions,
+    scores: HashMap<(usize, usize), f64>,
+    best: Option<(usize, usize, f64, ArimaModel)>,
+    failure: Option<Error>, // Ultimo ajuste fallido, para informar si ninguno funciona
+}
+
+impl Search<'_> {
+    // Ajusta (p, q) si no se ha hecho antes; devuelve `true` si es el nuevo mejor.
+    // Un orden demasiado grande para la serie o un ajuste que falla solo se
+    // descartan; cualquier otro error de datos afecta a todos los candidatos
+    // y se propaga.
+    fn try_order(&mut self, p: usize, q: usize) -> Result<bool> {
+        if self.scores.contains_key(&(p, q)) {
+            return Ok(false);
+        }
+        let spec = ArimaSpec::new(p, self.d, q);
+        if self.series.len() < spec.min_observations(0) {
+            self.failure = Some(Error::Data(format!(
+                "Not enough data points for ARIMA({},{},{})",
+                p, self.d, q
+            )));
+            return Ok(false);
+        }
+        let model = match ArimaModel::fit_spec(self.series, &spec) {
+            Ok(model) => model,
+            Err(e @ Error::Data(_)) => return Err(e),
+            Err(e) => {
+                self.failure = Some(e);
+                return Ok(false);
+            }
+        };
+        let score = self.options.criterion.score(&model);
+        if !score.is_finite() {
+            self.failure = Some(Error::Optimization(format!(
+                "ARIMA({},{},{}) has a non-finite information criterion",
+                p, self.d, q
+            )));
+            return Ok(false);
+        }
+        self.scores.insert((p, q), score);
+
+        let is_best = match &self.best {
+            Some((_, _, best, _)) => score < *best,
+            None => true,
+        };
+        if is_best {
+            self.best = Some((p, q, score, model));
+        }
+        Ok(is_best)
+    }
+}
+
+#[test]
+fn test_auto_arima_picks_ar1() {
+    use super::test_util::Lcg;
+    let mut rng = Lcg::new(99);
+    let mut value = 0.0;
+    let series: Vec<f64> = (0..600)
+        .map(|_| {
+            let noise = rng.noise();
+            value = 0.7 * value + noise;
+            value
+        })
+        .collect();
+
+    let options = AutoArimaOptions {
+        max_p: 3,
+        max_q: 2,
+        criterion: InformationCriterion::Bic,
+        ..AutoArimaOptions::default()
+    };
+    let result = auto_arima(&series, &options).unwrap();
+    let best = &result.candidates[0];
+    assert_eq!((best.p, best.d, best.q), (1, 0, 0));
+    assert_eq!(result.model.ar_coeffs.len(), 1);
+}
+
+#[test]
+fn test_auto_arima_propagates_data_errors() {
+    let options = AutoArimaOptions {
+        max_d: 0,
+        ..AutoArimaOptions::default()
+    };
+
+    // Un faltante invalida todos los ordenes: se informa en lugar de descartarlos
+    let mut gaps: Vec<f64> = (0..50).map(|t| (t as f64).sin()).collect();
+    gaps[10] = f64::NAN;
+    let error = auto_arima(&gaps, &options).err();
+    assert!(matches!(error, Some(Error::Data(msg)) if msg.contains("missing values")));
+
+    // Los ordenes que no caben en una serie corta se saltan sin abortar
+    let short: Vec<f64> = (0..6).map(|t| (t as f64 * 1.7).sin()).collect();
+    let result = auto_arima(&short, &options).unwrap();
+    assert!(result.candidates.iter().all(|c| c.p + c.q <= 2));
+
+    // Si ninguno se puede ajustar, el error dice por que
+    let error = auto_arima(&[1.0], &options).err();
+    assert!(matches!(error, Some(Error::Data(msg)) if msg.starts_with("Not enough data points")));
+}
+
+#[test]
+fn test_select_d_keeps_stationary_series_at_ten_percent() {
+    use super::test_util::Lcg;
+
+    // AR(1) estacionario: ninguna prueba debe pedir diferenciar al 10%
+    let mut rng = Lcg::new(7);
+    let mut value = 0.0;
+    let series: Vec<f64> = (0..500)
+        .map(|_| {
+            value = 0.5 * value + rng.noise();
+            value
+        })
+        .collect();
+    assert_eq!(select_d(&series, UnitRootTest::Kpss, 0.10, 2).unwrap(), 0);
+    assert_eq!(select_d(&series, UnitRootTest::Adf, 0.10, 2).unwrap(), 0);
+
+    // La caminata aleatoria correspondiente si necesita una diferencia
+    let walk: Vec<f64> = series
+        .iter()
+        .scan(0.0, |level, e| {
+            *level += e;
+            Some(*level)
+        })
+        .collect();
+    assert_eq!(select_d(&walk, UnitRootTest::Adf, 0.10, 2).unwrap(), 1);
+}
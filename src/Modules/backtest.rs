@@ -0,0 +1,267 @@
+use super::arima::{ArimaModel, ArimaSpec};
+use super::error::{Error, Result};
+use super::forecaster::Forecaster;
+
+/// Ventana de entrenamiento en cada origen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Window {
+    Expanding,      // Desde el inicio de la serie hasta el origen
+    Sliding(usize), // Solo las ultimas `n` observaciones antes del origen
+}
+
+/// Opciones de la evaluacion con origen rodante
+#[derive(Debug, Clone)]
+pub struct BacktestOptions {
+    pub window: Window,
+    pub initial_train: usize, // Observaciones antes del primer origen
+    pub horizon: usize,       // Pasos pronosticados en cada origen
+    pub step: usize,          // Observaciones entre origenes consecutivos
+    pub refit_every: usize,   // Reajusta cada tantos origenes; entre medio solo se actualiza
+}
+
+impl Default for BacktestOptions {
+    fn default() -> Self {
+        BacktestOptions {
+            window: Window::Expanding,
+            initial_train: 100,
+            horizon: 10,
+            step: 1,
+            refit_every: 1,
+        }
+    }
+}
+
+/// Pronostico hecho en un origen junto con lo observado despues
+#[derive(Debug, Clone)]
+pub struct ForecastRecord {
+    pub origin: usize, // Indice de la primera observacion pronosticada
+    pub refitted: bool,
+    pub forecast: Vec<f64>,
+    pub actual: Vec<f64>,
+}
+
+/// Metricas agregadas sobre todos los origenes para un horizonte
+#[derive(Debug, Clone, PartialEq)]
+pub struct HorizonMetrics {
+    pub horizon: usize, // 1 = un paso adelante
+    pub count: usize,
+    pub me: f64,
+    pub mae: f64,
+    pub rmse: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct BacktestResult {
+    pub records: Vec<ForecastRecord>,
+    pub per_horizon: Vec<HorizonMetrics>,
+    pub refits: usize,
+}
+
+impl BacktestResult {
+    /// Errores (actual - pronostico) de todos los origenes para un horizonte
+    pub fn errors_at(&self, horizon: usize) -> Vec<f64> {
+        self.records
+            .iter()
+            .filter_map(|r| Some(r.actual.get(horizon - 1)? - r.forecast.get(horizon - 1)?))
+            .collect()
+    }
+}
+
+fn check_options(series_len: usize, options: &BacktestOptions) -> Result<()> {
+    if options.horizon == 0 || options.step == 0 || options.refit_every == 0 {
+        return Err(Error::Evaluation(
+            "Horizon, step and refit frequency must be positive".into(),
+        ));
+    }
+    if options.initial_train == 0 {
+        return Err(Error::Evaluation(
+            "Initial training window must not be empty".into(),
+        ));
+    }
+    if let Window::Sliding(size) = options.window
+        && (size == 0 || size > options.initial_train)
+    {
+        return Err(Error::Evaluation(
+            "Sliding window must be between 1 and the initial training size".into(),
+        ));
+    }
+    if options.initial_train + options.horizon > series_len {
+        return Err(Error::Evaluation(format!(
+            "Series of length {} is too short for {} training and {} horizon",
+            series_len, options.initial_train, options.horizon
+        )));
+    }
+    Ok(())
+}
+
+/// Evaluacion con origen rodante para cualquier pronosticador
+///
+/// Parameters:
+/// - series: la serie completa
+/// - options: ventana, horizonte, paso entre origenes y frecuencia de reajuste
+/// - fit: ajusta un modelo con la ventana de entrenamiento
+/// - forecast: recibe el modelo, las observaciones nuevas desde la llamada
+///   anterior (vacio justo despues de ajustar) y el horizonte
+///
+/// Returns:
+/// - Los pronosticos de cada origen y las metricas por horizonte
+pub fn backtest<M, F, P>(
+    series: &[f64],
+    options: &BacktestOptions,
+    mut fit: F,
+    mut forecast: P,
+) -> Result<BacktestResult>
+where
+    F: FnMut(&[f64]) -> Result<M>,
+    P: FnMut(&mut M, &[f64], usize) -> Result<Vec<f64>>,
+{
+    check_options(series.len(), options)?;
+
+    let mut records = Vec::new();
+    let mut model: Option<M> = None;
+    let mut seen = 0; // Observaciones que el modelo actual ya conoce
+    let mut refits = 0;
+
+    let origins = (options.initial_train..=series.len() - options.horizon).step_by(options.step);
+    for (k, origin) in origins.enumerate() {
+        let refitted = model.is_none() || k % options.refit_every == 0;
+        if refitted {
+            let start = match options.window {
+                Window::Expanding => 0,
+                Window::Sliding(size) => origin - size,
+            };
+            model = Some(fit(&series[start..origin])?);
+            seen = origin;
+            refits += 1;
+        }
+        let current = model
+            .as_mut()
+            .ok_or_else(|| Error::Evaluation("Model was not fitted".into()))?;
+        let predicted = forecast(current, &series[seen..origin], options.horizon)?;
+        seen = origin;
+
+        if predicted.len() != options.horizon {
+            return Err(Error::Evaluation(format!(
+                "Forecaster returned {} values for horizon {}",
+                predicted.len(),
+                options.horizon
+            )));
+        }
+        records.push(ForecastRecord {
+            origin,
+            refitted,
+            forecast: predicted,
+            actual: series[origin..origin + options.horizon].to_vec(),
+        });
+    }
+
+    let mut result = BacktestResult {
+        records,
+        per_horizon: Vec::new(),
+        refits,
+    };
+    result.per_horizon = (1..=options.horizon)
+        .map(|h| {
+            let errors = result.errors_at(h);
+            let n = errors.len() as f64;
+            HorizonMetrics {
+                horizon: h,
+                count: errors.len(),
+                me: errors.iter().sum::<f64>() / n,
+                mae: errors.iter().map(|e| e.abs()).sum::<f64>() / n,
+                rmse: (errors.iter().map(|e| e * e).sum::<f64>() / n).sqrt(),
+            }
+        })
+        .collect();
+    Ok(result)
+}
+
+/// Backtest de cualquier `Forecaster`: se ajusta con `fit` en cada reajuste
+/// y entre reajustes incorpora las observaciones nuevas con `update`
+pub fn backtest_forecaster<M, F>(
+    series: &[f64],
+    options: &BacktestOptions,
+    fit: F,
+) -> Result<BacktestResult>
+where
+    M: Forecaster,
+    F: FnMut(&[f64]) -> Result<M>,
+{
+    backtest(series, options, fit, |model, new, horizon| {
+        for &y in new {
+            model.update(y)?;
+        }
+        Ok(model.forecast(horizon))
+    })
+}
+
+/// Backtest de un ARIMA ajustado con `spec` en cada reajuste
+pub fn backtest_arima(
+    series: &[f64],
+    spec: &ArimaSpec,
+    options: &BacktestOptions,
+) -> Result<BacktestResult> {
+    backtest_forecaster(series, options, |train| ArimaModel::fit_spec(train, spec))
+}
+
+#[test]
+fn test_backtest_error_grows_with_horizon() {
+    use super::ets::{EtsModel, EtsSpec};
+    use super::test_util::Lcg;
+
+    // Ultimo valor sobre una recta: el error en el horizonte h es h * pendiente
+    let line: Vec<f64> = (0..50).map(|t| 2.0 * t as f64).collect();
+    let options = BacktestOptions {
+        window: Window::Sliding(5),
+        initial_train: 10,
+        horizon: 3,
+        step: 2,
+        refit_every: 3,
+    };
+    let naive = backtest(
+        &line,
+        &options,
+        |train| Ok(*train.last().unwrap()),
+        |last, new, horizon| {
+            if let Some(y) = new.last() {
+                *last = *y;
+            }
+            Ok(vec![*last; horizon])
+        },
+    )
+    .unwrap();
+    assert_eq!(naive.records.len(), 19); // Origenes 10, 12, ..., 46
+    assert_eq!(naive.refits, 7);
+    for m in &naive.per_horizon {
+        assert!((m.mae - 2.0 * m.horizon as f64).abs() < 1e-12);
+        assert_eq!(m.me, m.mae);
+    }
+
+    // Caminata aleatoria: el RMSE crece con el horizonte
+    let mut rng = Lcg::new(7);
+    let mut level = 0.0;
+    let walk: Vec<f64> = (0..400)
+        .map(|_| {
+            level += rng.noise();
+            level
+        })
+        .collect();
+    let options = BacktestOptions {
+        initial_train: 200,
+        horizon: 8,
+        step: 4,
+        refit_every: 10,
+        ..BacktestOptions::default()
+    };
+    let result = backtest_arima(&walk, &ArimaSpec::new(0, 1, 0), &options).unwrap();
+    let rmse: Vec<f64> = result.per_horizon.iter().map(|m| m.rmse).collect();
+    assert!(rmse[7] > 1.5 * rmse[0]);
+
+    // El mismo backtest con suavizado exponencial simple a traves de `Forecaster`
+    let ses = backtest_forecaster(&walk, &options, |train| {
+        EtsModel::fit(train, &EtsSpec::simple())
+    })
+    .unwrap();
+    assert_eq!(ses.records.len(), result.records.len());
+    assert!(ses.per_horizon[7].rmse > 1.5 * ses.per_horizon[0].rmse);
+}
@@ -0,0 +1,513 @@
+use super::arima::ArimaModel;
+use super::error::{Error, Result};
+use std::fmt::Write;
+
+/// Tipo numerico del codigo generado
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scalar {
+    F32, // ESP32 / Cortex-M4F: FPU de precision simple
+    F64, // Para comparar bit a bit contra el modelo del host
+}
+
+impl Scalar {
+    fn rust_name(&self) -> &'static str {
+        match self {
+            Scalar::F32 => "f32",
+            Scalar::F64 => "f64",
+        }
+    }
+
+    fn c_name(&self) -> &'static str {
+        match self {
+            Scalar::F32 => "float",
+            Scalar::F64 => "double",
+        }
+    }
+
+    // Literal con todos los digitos necesarios para reproducir el valor
+    fn literal(&self, value: f64) -> String {
+        match self {
+            Scalar::F32 => format!("{:?}", value as f32),
+            Scalar::F64 => format!("{:?}", value),
+        }
+    }
+
+    fn c_literal(&self, value: f64) -> String {
+        match self {
+            Scalar::F32 => format!("{}f", self.literal(value)),
+            Scalar::F64 => self.literal(value),
+        }
+    }
+}
+
+// Descripcion corta del modelo para el encabezado del archivo generado
+fn describe(model: &ArimaModel) -> String {
+    let spec = model.spec();
+    let mut name = format!("ARIMA({},{},{})", spec.p, spec.d, spec.q);
+    if let Some(s) = spec.seasonal {
+        name = format!("S{}({},{},{}){}", name, s.p, s.d, s.q, s.period);
+    }
+    name
+}
+
+fn check_supported(model: &ArimaModel) -> Result<()> {
+    if !model.exog_coeffs.is_empty() {
+        return Err(Error::Model(
+            "Code generation does not support exogenous regressors".into(),
+        ));
+    }
+    Ok(())
+}
+
+fn rust_array(name: &str, values: &[f64], scalar: Scalar, public: bool) -> String {
+    let items: Vec<String> = values.iter().map(|v| scalar.literal(*v)).collect();
+    format!(
+        "{}const {}: [Scalar; {}] = [{}];\n",
+        if public { "pub " } else { "" },
+        name,
+        values.len(),
+        items.join(", ")
+    )
+}
+
+/// Genera un modulo Rust `no_std` autocontenido con el predictor del modelo
+///
+/// El modulo no usa `std` ni memoria dinamica: los coeficientes son arreglos
+/// de tamano fijo y el estado vive en buffers circulares. Arranca desde el
+/// estado del modelo al final del entrenamiento.
+///
+/// Returns:
+/// - El codigo fuente listo para guardarse como `arima_model.rs` en el firmware
+pub fn generate_rust_module(model: &ArimaModel, scalar: Scalar) -> Result<String> {
+    check_supported(model)?;
+    let parts = model.predictor_parts();
+
+    let mut code = String::new();
+    writeln!(
+        code,
+        "// Generado por embebidos_limpo a partir de un {} -- no editar a mano",
+        describe(model)
+    )?;
+    code.push_str("// Predictor sin std ni asignaciones dinamicas\n\n");
+    writeln!(code, "pub type Scalar = {};\n", scalar.rust_name())?;
+    code.push_str(&rust_array("AR", &parts.ar, scalar, true));
+    code.push_str(&rust_array("MA", &parts.ma, scalar, true));
+    code.push_str(&rust_array("DELTA", &parts.delta, scalar, true));
+    writeln!(
+        code,
+        "pub const MEAN: Scalar = {};\n",
+        scalar.literal(parts.mean)
+    )?;
+    code.push_str(&rust_array("INIT_LEVELS", &parts.levels, scalar, false));
+    code.push_str(&rust_array("INIT_VALUES", &parts.values, scalar, false));
+    code.push_str(&rust_array(
+        "INIT_RESIDUALS",
+        &parts.residuals,
+        scalar,
+        false,
+    ));
+    code.push_str(RUST_PREDICTOR);
+    Ok(code)
+}
+
+// Parte fija del modulo generado. Las operaciones siguen el mismo orden que
+// el motor de pronostico del host para que en f64 los resultados coincidan.
+const RUST_PREDICTOR: &str = r#"
+// Buffer circular: lag(0) es el valor mas reciente
+#[derive(Clone, Copy)]
+struct Ring<const N: usize> {
+    data: [Scalar; N],
+    head: usize,
+}
+
+impl<const N: usize> Ring<N> {
+    const fn new(data: [Scalar; N]) -> Self {
+        Ring { data, head: 0 }
+    }
+
+    fn lag(&self, j: usize) -> Scalar {
+        self.data[(self.head + N - 1 - j) % N]
+    }
+
+    fn push(&mut self, value: Scalar) {
+        if N > 0 {
+            self.data[self.head] = value;
+            self.head = (self.head + 1) % N;
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Predictor {
+    levels: Ring<{ DELTA.len() }>,
+    values: Ring<{ AR.len() }>,
+    residuals: Ring<{ MA.len() }>,
+}
+
+impl Default for Predictor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Predictor {
+    pub const fn new() -> Self {
+        Predictor {
+            levels: Ring::new(INIT_LEVELS),
+            values: Ring::new(INIT_VALUES),
+            residuals: Ring::new(INIT_RESIDUALS),
+        }
+    }
+
+    fn predict_value(&self) -> Scalar {
+        let mut prediction = MEAN;
+        for (j, coeff) in AR.iter().enumerate() {
+            prediction += coeff * (self.values.lag(j) - MEAN);
+        }
+        for (j, coeff) in MA.iter().enumerate() {
+            prediction += coeff * self.residuals.lag(j);
+        }
+        prediction
+    }
+
+    fn integrate(&self, value: Scalar) -> Scalar {
+        let mut level = value;
+        for (k, coeff) in DELTA.iter().enumerate() {
+            level += coeff * self.levels.lag(k);
+        }
+        level
+    }
+
+    /// Pronostico del siguiente paso
+    pub fn predict(&self) -> Scalar {
+        self.integrate(self.predict_value())
+    }
+
+    /// Agrega una lectura del sensor y devuelve el pronostico del siguiente paso
+    pub fn update(&mut self, observation: Scalar) -> Scalar {
+        let value = observation - self.integrate(0.0);
+        let residual = value - self.predict_value();
+        self.levels.push(observation);
+        self.values.push(value);
+        self.residuals.push(residual);
+        self.predict()
+    }
+
+    /// Pronostico de varios pasos, uno por elemento de `out`
+    pub fn forecast(&self, out: &mut [Scalar]) {
+        let mut state = *self;
+        for slot in out.iter_mut() {
+            let value = state.predict_value();
+            let level = state.integrate(value);
+            state.levels.push(level);
+            state.values.push(value);
+            state.residuals.push(0.0);
+            *slot = level;
+        }
+    }
+}
+"#;
+
+fn c_array(name: &str, values: &[f64], scalar: Scalar) -> String {
+    // C no permite arreglos de tamano cero
+    let items: Vec<String> = if values.is_empty() {
+        vec![scalar.c_literal(0.0)]
+    } else {
+        values.iter().map(|v| scalar.c_literal(*v)).collect()
+    };
+    format!(
+        "static const {} {}[{}] = {{{}}};\n",
+        scalar.c_name(),
+        name,
+        items.len(),
+        items.join(", ")
+    )
+}
+
+/// Genera un header C equivalente (funciones `static inline`)
+///
+/// Parameters:
+/// - prefix: prefijo de los simbolos, por ejemplo "temp" da `temp_update`
+pub fn generate_c_header(model: &ArimaModel, prefix: &str, scalar: Scalar) -> Result<String> {
+    check_supported(model)?;
+    if prefix.is_empty()
+        || !prefix
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        || prefix.starts_with(|c: char| c.is_ascii_digit())
+    {
+        return Err(Error::Model(format!(
+            "Invalid C identifier prefix: {}",
+            prefix
+        )));
+    }
+
+    let parts = model.predictor_parts();
+    let upper = prefix.to_ascii_uppercase();
+    let t = scalar.c_name();
+    let sizes = [
+        ("LEVELS", parts.delta.len()),
+        ("AR", parts.ar.len()),
+        ("MA", parts.ma.len()),
+    ];
+
+    let mut code = String::new();
+    writeln!(
+        code,
+        "/* Generado por embebidos_limpo a partir de un {} -- no editar a mano */",
+        describe(model)
+    )?;
+    writeln!(
+        code,
+        "#ifndef {}_ARIMA_H\n#define {}_ARIMA_H\n",
+        upper, upper
+    )?;
+    code.push_str("#include <stddef.h>\n\n");
+    for (name, len) in sizes {
+        writeln!(code, "#define {}_{}_LEN {}", upper, name, len)?;
+    }
+    code.push('\n');
+    code.push_str(&c_array(&format!("{}_AR", upper), &parts.ar, scalar));
+    code.push_str(&c_array(&format!("{}_MA", upper), &parts.ma, scalar));
+    code.push_str(&c_array(&format!("{}_DELTA", upper), &parts.delta, scalar));
+    writeln!(
+        code,
+        "static const {} {}_MEAN = {};\n",
+        t,
+        upper,
+        scalar.c_literal(parts.mean)
+    )?;
+    code.push_str(&c_array(
+        &format!("{}_INIT_LEVELS", upper),
+        &parts.levels,
+        scalar,
+    ));
+    code.push_str(&c_array(
+        &format!("{}_INIT_VALUES", upper),
+        &parts.values,
+        scalar,
+    ));
+    code.push_str(&c_array(
+        &format!("{}_INIT_RESIDUALS", upper),
+        &parts.residuals,
+        scalar,
+    ));
+
+    let body = C_PREDICTOR
+        .replace("{T}", t)
+        .replace("{P}", prefix)
+        .replace("{U}", &upper);
+    code.push_str(&body);
+    writeln!(code, "\n#endif /* {}_ARIMA_H */", upper)?;
+    Ok(code)
+}
+
+// Parte fija del header C; {T} es el tipo, {P} el prefijo y {U} el prefijo
+// en mayusculas. Los bucles sobre coeficientes que pueden faltar (d = 0,
+// p = 0 o q = 0) van entre #if: con largo 0 la comparacion siempre falsa
+// dispara -Wtype-limits.
+const C_PREDICTOR: &str = r#"
+typedef struct {
+    {T} levels[sizeof({U}_INIT_LEVELS) / sizeof({T})];
+    {T} values[sizeof({U}_INIT_VALUES) / sizeof({T})];
+    {T} residuals[sizeof({U}_INIT_RESIDUALS) / sizeof({T})];
+    size_t levels_head, values_head, residuals_head;
+} {P}_predictor_t;
+
+static inline {T} {P}_lag(const {T} *buf, size_t len, size_t head, size_t j) {
+    return buf[(head + len - 1 - j) % len];
+}
+
+static inline void {P}_push({T} *buf, size_t len, size_t *head, {T} value) {
+    if (len > 0) {
+        buf[*head] = value;
+        *head = (*head + 1) % len;
+    }
+}
+
+static inline void {P}_init({P}_predictor_t *s) {
+    size_t i;
+    for (i = 0; i < sizeof(s->levels) / sizeof({T}); i++) s->levels[i] = {U}_INIT_LEVELS[i];
+    for (i = 0; i < sizeof(s->values) / sizeof({T}); i++) s->values[i] = {U}_INIT_VALUES[i];
+    for (i = 0; i < sizeof(s->residuals) / sizeof({T}); i++) s->residuals[i] = {U}_INIT_RESIDUALS[i];
+    s->levels_head = s->values_head = s->residuals_head = 0;
+}
+
+static inline {T} {P}_predict_value(const {P}_predictor_t *s) {
+    {T} prediction = {U}_MEAN;
+#if {U}_AR_LEN > 0
+    for (size_t j = 0; j < {U}_AR_LEN; j++)
+        prediction += {U}_AR[j] * ({P}_lag(s->values, {U}_AR_LEN, s->values_head, j) - {U}_MEAN);
+#endif
+#if {U}_MA_LEN > 0
+    for (size_t j = 0; j < {U}_MA_LEN; j++)
+        prediction += {U}_MA[j] * {P}_lag(s->residuals, {U}_MA_LEN, s->residuals_head, j);
+#endif
+    (void)s;
+    return prediction;
+}
+
+static inline {T} {P}_integrate(const {P}_predictor_t *s, {T} value) {
+    {T} level = value;
+#if {U}_LEVELS_LEN > 0
+    for (size_t k = 0; k < {U}_LEVELS_LEN; k++)
+        level += {U}_DELTA[k] * {P}_lag(s->levels, {U}_LEVELS_LEN, s->levels_head, k);
+#endif
+    (void)s;
+    return level;
+}
+
+/* Pronostico del siguiente paso */
+static inline {T} {P}_predict(const {P}_predictor_t *s) {
+    return {P}_integrate(s, {P}_predict_value(s));
+}
+
+/* Agrega una lectura y devuelve el pronostico del siguiente paso */
+static inline {T} {P}_update({P}_predictor_t *s, {T} observation) {
+    {T} value = observation - {P}_integrate(s, 0);
+    {T} residual = value - {P}_predict_value(s);
+    {P}_push(s->levels, {U}_LEVELS_LEN, &s->levels_head, observation);
+    {P}_push(s->values, {U}_AR_LEN, &s->values_head, value);
+    {P}_push(s->residuals, {U}_MA_LEN, &s->residuals_head, residual);
+    return {P}_predict(s);
+}
+
+/* Pronostico de `steps` pasos en `out`, sin modificar el estado */
+static inline void {P}_forecast(const {P}_predictor_t *s, {T} *out, size_t steps) {
+    {P}_predictor_t state = *s;
+    size_t h;
+    for (h = 0; h < steps; h++) {
+        {T} value = {P}_predict_value(&state);
+        {T} level = {P}_integrate(&state, value);
+        {P}_push(state.levels, {U}_LEVELS_LEN, &state.levels_head, level);
+        {P}_push(state.values, {U}_AR_LEN, &state.values_head, value);
+        {P}_push(state.residuals, {U}_MA_LEN, &state.residuals_head, 0);
+        out[h] = level;
+    }
+}
+"#;
+
+#[test]
+fn test_generated_code_matches_host_model() {
+    use super::arima::ArimaSpec;
+    use std::path::Path;
+    use std::process::Command;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    let series: Vec<f64> = (0..300)
+        .map(|t| 25.0 + (t as f64 / 12.0).sin() + 0.3 * (t as f64 / 3.0).cos())
+        .collect();
+    let inputs: Vec<String> = series[250..].iter().map(|v| format!("{:?}", v)).collect();
+
+    // Directorio propio de esta ejecucion para no chocar con tests en paralelo
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    let dir = std::env::temp_dir().join(format!(
+        "embebidos_codegen_{}_{}",
+        std::process::id(),
+        nanos
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let run = |binary: &Path| -> Vec<f64> {
+        let output = Command::new(binary).output().unwrap();
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .lines()
+            .map(|l| l.parse().unwrap())
+            .collect()
+    };
+    let check = |scalar: Scalar, got: &[f64], expected: &[f64]| {
+        assert_eq!(got.len(), expected.len());
+        for (g, e) in got.iter().zip(expected) {
+            match scalar {
+                // Mismo orden de operaciones que el host: bit a bit
+                Scalar::F64 => assert_eq!(g.to_bits(), e.to_bits()),
+                Scalar::F32 => assert!((g - e).abs() < 1e-3 * e.abs(), "{} vs {}", g, e),
+            }
+        }
+    };
+
+    // Con y sin diferenciacion, sin parte MA y sin parte AR
+    for (p, d, q) in [(2, 1, 1), (1, 0, 0), (0, 1, 1), (2, 0, 0)] {
+        let model = ArimaModel::fit_spec(&series[..250], &ArimaSpec::new(p, d, q)).unwrap();
+
+        // Referencia del host: pronosticos a un paso y luego 5 pasos hacia adelante
+        let mut host = model.clone();
+        let mut expected: Vec<f64> = series[250..]
+            .iter()
+            .map(|&y| host.update(y).unwrap())
+            .collect();
+        expected.extend(host.forecast(5));
+
+        for scalar in [Scalar::F64, Scalar::F32] {
+            let name = format!("{}_{}{}{}", scalar.rust_name(), p, d, q);
+
+            // Programa Rust que alimenta las mismas lecturas e imprime los pronosticos
+            let crate_dir = dir.join(&name);
+            std::fs::create_dir_all(&crate_dir).unwrap();
+            let module = generate_rust_module(&model, scalar).unwrap();
+            std::fs::write(crate_dir.join("arima_model.rs"), module).unwrap();
+            let main = format!(
+                "mod arima_model;\nuse arima_model::{{Predictor, Scalar}};\nfn main() {{\n    \
+                 let mut p = Predictor::new();\n    \
+                 for y in [{}] {{ println!(\"{{:?}}\", p.update(y as Scalar)); }}\n    \
+                 let mut out = [0.0; 5];\n    p.forecast(&mut out);\n    \
+                 for v in out {{ println!(\"{{:?}}\", v); }}\n}}\n",
+                inputs.join(", ")
+            );
+            let source = crate_dir.join("main.rs");
+            std::fs::write(&source, main).unwrap();
+            let binary = dir.join(format!("predictor_{}", name));
+            let status = Command::new("rustc")
+                .args(["--edition", "2021", "-A", "dead_code", "-o"])
+                .arg(&binary)
+                .arg(&source)
+                .status()
+                .unwrap();
+            assert!(status.success());
+            check(scalar, &run(&binary), &expected);
+
+            // El mismo programa en C con el header; se omite si no hay compilador
+            let header = generate_c_header(&model, "temp", scalar).unwrap();
+            let header_path = dir.join(format!("temp_arima_{}.h", name));
+            std::fs::write(&header_path, header).unwrap();
+            let c_main = format!(
+                "#include <stdio.h>\n#include \"temp_arima_{}.h\"\nint main(void) {{\n    \
+                 static const double inputs[] = {{{}}};\n    \
+                 {} out[5];\n    temp_predictor_t s;\n    size_t i;\n    temp_init(&s);\n    \
+                 for (i = 0; i < sizeof(inputs) / sizeof(inputs[0]); i++)\n        \
+                 printf(\"%.17g\\n\", (double)temp_update(&s, inputs[i]));\n    \
+                 temp_forecast(&s, out, 5);\n    \
+                 for (i = 0; i < 5; i++) printf(\"%.17g\\n\", (double)out[i]);\n    \
+                 return 0;\n}}\n",
+                name,
+                inputs.join(", "),
+                scalar.c_name()
+            );
+            let c_source = dir.join(format!("main_{}.c", name));
+            std::fs::write(&c_source, c_main).unwrap();
+            let c_binary = dir.join(format!("predictor_c_{}", name));
+            let Ok(status) = Command::new("cc")
+                .args([
+                    "-std=c99",
+                    "-Wall",
+                    "-Wextra",
+                    "-Werror",
+                    "-ffp-contract=off",
+                ])
+                .arg("-o")
+                .arg(&c_binary)
+                .arg(&c_source)
+                .status()
+            else {
+                continue;
+            };
+            assert!(status.success(), "C header for ARIMA({},{},{})", p, d, q);
+            check(scalar, &run(&c_binary), &expected);
+        }
+    }
+    std::fs::remove_dir_all(&dir).ok();
+}
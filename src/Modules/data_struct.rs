@@ -1,33 +1,459 @@
-use csv::Reader;
-use serde::Deserialize;
-use std::error::Error;
-use std::fs::File;
+use super::error::{Error, Result};
+use csv::{Reader, ReaderBuilder, StringRecord};
 
-#[derive(Debug, Deserialize, Clone)]
-pub struct Data {
-    Timestamp: u64,
-    Temperature: f64,
-    Humidity: f64,
+/// Nombre de la columna de tiempo que se detecta automaticamente en el encabezado
+pub const DEFAULT_TIMESTAMP_COLUMN: &str = "Timestamp";
+
+/// Esquema declarado de un archivo CSV de sensores
+///
+/// - timestamp: columna con el contador de milisegundos (opcional)
+/// - columns: columnas numericas que se van a cargar, en cualquier orden
+#[derive(Debug, Clone)]
+pub struct Schema {
+    pub timestamp: Option<String>,
+    pub columns: Vec<String>,
+}
+
+impl Schema {
+    pub fn new(timestamp: Option<&str>, columns: &[&str]) -> Schema {
+        Schema {
+            timestamp: timestamp.map(|t| t.to_string()),
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
+    // Descubre el esquema a partir del encabezado: todo lo que no sea la
+    // columna de tiempo se trata como numerico. Los nombres repetidos se
+    // rechazan igual que en `Dataset::new`
+    fn from_header(header: &StringRecord) -> Result<Schema> {
+        let mut timestamp = None;
+        let mut columns: Vec<String> = Vec::new();
+        for name in header.iter() {
+            let name = name.trim();
+            if columns.iter().chain(&timestamp).any(|c| c == name) {
+                return Err(Error::Data(format!("Duplicated column: {}", name)));
+            }
+            if timestamp.is_none() && name.eq_ignore_ascii_case(DEFAULT_TIMESTAMP_COLUMN) {
+                timestamp = Some(name.to_string());
+            } else {
+                columns.push(name.to_string());
+            }
+        }
+        Ok(Schema { timestamp, columns })
+    }
+}
+
+/// Conjunto de datos columnar: cada columna numerica se guarda como un vector
+/// independiente y el tiempo se mantiene como `u64` sin perder precision
+#[derive(Debug, Clone, Default)]
+pub struct Dataset {
+    timestamps: Option<Vec<u64>>,
+    timestamp_name: Option<String>,
+    names: Vec<String>,
+    columns: Vec<Vec<f64>>,
+}
+
+impl Dataset {
+    /// Construye un dataset a partir de columnas ya cargadas en memoria
+    pub fn new(timestamps: Option<Vec<u64>>, columns: Vec<(String, Vec<f64>)>) -> Result<Dataset> {
+        let len = timestamps
+            .as_ref()
+            .map(|t| t.len())
+            .or_else(|| columns.first().map(|(_, c)| c.len()))
+            .unwrap_or(0);
+
+        let mut names = Vec::with_capacity(columns.len());
+        let mut values = Vec::with_capacity(columns.len());
+        for (name, column) in columns {
+            if column.len() != len {
+                return Err(Error::Data(format!(
+                    "Column {} has {} rows, expected {}",
+                    name,
+                    column.len(),
+                    len
+                )));
+            }
+            if names.contains(&name) {
+                return Err(Error::Data(format!("Duplicated column: {}", name)));
+            }
+            names.push(name);
+            values.push(column);
+        }
+
+        let timestamp_name = timestamps
+            .as_ref()
+            .map(|_| DEFAULT_TIMESTAMP_COLUMN.to_string());
+
+        Ok(Dataset {
+            timestamps,
+            timestamp_name,
+            names,
+            columns: values,
+        })
+    }
+
+    /// Numero de filas
+    pub fn len(&self) -> usize {
+        match &self.timestamps {
+            Some(t) => t.len(),
+            None => self.columns.first().map_or(0, |c| c.len()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Nombres de las columnas numericas (sin incluir la de tiempo)
+    pub fn columns(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Contador de tiempo crudo, si el archivo lo incluye
+    pub fn timestamps(&self) -> Option<&[u64]> {
+        self.timestamps.as_deref()
+    }
+
+    /// Columna numerica por nombre, `None` si no existe
+    pub fn column(&self, name: &str) -> Option<&[f64]> {
+        self.names
+            .iter()
+            .position(|n| n == name)
+            .map(|i| self.columns[i].as_slice())
+    }
+
+    /// Cantidad de valores faltantes (NaN) en una columna
+    pub fn missing_count(&self, name: &str) -> Option<usize> {
+        self.column(name)
+            .map(|c| c.iter().filter(|v| v.is_nan()).count())
+    }
+
+    /// Copia de las filas en el rango [start, end)
+    pub fn slice(&self, start: usize, end: usize) -> Dataset {
+        let end = end.min(self.len());
+        let start = start.min(end);
+        Dataset {
+            timestamps: self.timestamps.as_ref().map(|t| t[start..end].to_vec()),
+            timestamp_name: self.timestamp_name.clone(),
+            names: self.names.clone(),
+            columns: self
+                .columns
+                .iter()
+                .map(|c| c[start..end].to_vec())
+                .collect(),
+        }
+    }
+
+    /// Divide el dataset en dos partes, util para entrenamiento/prueba
+    pub fn split_at(&self, index: usize) -> (Dataset, Dataset) {
+        (self.slice(0, index), self.slice(index, self.len()))
+    }
+}
+
+/// Que hacer con una fila que trae valores invalidos o faltantes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BadRowPolicy {
+    Skip,        // Descarta la fila completa
+    MarkMissing, // Conserva la fila y deja NaN en los campos malos
+}
+
+/// Opciones del modo tolerante de lectura
+///
+/// - policy: que hacer con las filas malas
+/// - sentinels: codigos de error del sensor que se tratan como faltantes
+#[derive(Debug, Clone)]
+pub struct ReadOptions {
+    pub policy: BadRowPolicy,
+    pub sentinels: Vec<f64>,
+}
+
+impl Default for ReadOptions {
+    fn default() -> Self {
+        ReadOptions {
+            policy: BadRowPolicy::MarkMissing,
+            sentinels: vec![-999.0],
+        }
+    }
 }
 
-pub fn read_csv(filepath: &str) -> Result<Vec<Data>, Box<dyn Error>> {
+/// Problema encontrado en una linea del archivo
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowIssue {
+    pub line: usize,
+    pub column: Option<String>,
+    pub reason: String,
+}
+
+/// Resumen de una lectura tolerante
+#[derive(Debug, Clone, Default)]
+pub struct LoadReport {
+    pub rows_read: usize,
+    pub rows_kept: usize,
+    pub rows_skipped: usize,
+    pub missing_values: usize,
+    pub issues: Vec<RowIssue>,
+}
+
+/// Lee un CSV descubriendo las columnas a partir del encabezado
+pub fn read_csv(filepath: &str) -> Result<Dataset> {
     let mut reader = Reader::from_path(filepath)?;
-    let mut data_vec: Vec<Data> = Vec::new();
+    let schema = Schema::from_header(reader.headers()?)?;
+    load_records(&mut reader, &schema, None).map(|(data, _)| data)
+}
+
+/// Lee un CSV cargando solo las columnas declaradas en el esquema
+pub fn read_csv_with_schema(filepath: &str, schema: &Schema) -> Result<Dataset> {
+    let mut reader = Reader::from_path(filepath)?;
+    load_records(&mut reader, schema, None).map(|(data, _)| data)
+}
+
+/// Lee un CSV sin abortar por filas malas (lineas truncadas, `nan`, -999...)
+///
+/// Returns:
+/// - El dataset con NaN en los valores faltantes y el reporte por linea
+pub fn read_csv_lenient(filepath: &str, options: &ReadOptions) -> Result<(Dataset, LoadReport)> {
+    let mut reader = ReaderBuilder::new().flexible(true).from_path(filepath)?;
+    let schema = Schema::from_header(reader.headers()?)?;
+    load_records(&mut reader, &schema, Some(options))
+}
+
+/// Version tolerante de `read_csv_with_schema`
+pub fn read_csv_lenient_with_schema(
+    filepath: &str,
+    schema: &Schema,
+    options: &ReadOptions,
+) -> Result<(Dataset, LoadReport)> {
+    let mut reader = ReaderBuilder::new().flexible(true).from_path(filepath)?;
+    load_records(&mut reader, schema, Some(options))
+}
+
+// Resuelve la posicion de cada columna del esquema dentro del encabezado
+fn column_index(header: &StringRecord, name: &str) -> Result<usize> {
+    header
+        .iter()
+        .position(|h| h.trim() == name)
+        .ok_or_else(|| Error::Data(format!("Column {} not found in CSV header", name)))
+}
+
+// Interpreta un campo numerico; `Err` lleva el motivo del rechazo. La
+// lectura estricta acepta `nan` e `inf` como siempre; la tolerante los
+// trata como faltantes
+fn parse_value(raw: &str, options: Option<&ReadOptions>) -> Result<f64, String> {
+    if raw.is_empty() {
+        return Err("missing value".to_string());
+    }
+    let value = raw
+        .parse::<f64>()
+        .map_err(|_| format!("invalid value '{}'", raw))?;
+    if let Some(options) = options {
+        if !value.is_finite() {
+            return Err(format!("non-finite value '{}'", raw));
+        }
+        if options.sentinels.contains(&value) {
+            return Err(format!("sensor error code {}", raw));
+        }
+    }
+    Ok(value)
+}
 
-    // Lee cada fila del csv y lo convierte a struct
-    for line in reader.deserialize() {
-        let row: Data = line?;
-        data_vec.push(row); //Crea una lista de datos
+// Recorre las filas; con `options == None` la primera fila mala aborta la
+// lectura, con opciones se anota en el reporte y se sigue
+fn load_records<R: std::io::Read>(
+    reader: &mut Reader<R>,
+    schema: &Schema,
+    options: Option<&ReadOptions>,
+) -> Result<(Dataset, LoadReport)> {
+    let header = reader.headers()?.clone();
+    let ts_index = match &schema.timestamp {
+        Some(name) => Some(column_index(&header, name)?),
+        None => None,
+    };
+    let indices = schema
+        .columns
+        .iter()
+        .map(|name| column_index(&header, name))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut timestamps = ts_index.map(|_| Vec::new());
+    let mut columns: Vec<Vec<f64>> = vec![Vec::new(); indices.len()];
+    let mut report = LoadReport::default();
+    let mut row_values = vec![0.0; indices.len()];
+
+    // Lee cada fila del csv y la reparte en sus columnas
+    for (row, record) in reader.records().enumerate() {
+        // Linea donde empieza el registro segun el lector; un campo entre
+        // comillas puede ocupar varias lineas. Sin posicion se supone una
+        // linea por registro despues del encabezado
+        let position = match &record {
+            Ok(record) => record.position(),
+            Err(e) => e.position(),
+        };
+        let line = position.map_or(row + 2, |p| p.line() as usize);
+        report.rows_read += 1;
+
+        let record = match (record, options) {
+            (Ok(record), _) => record,
+            (Err(e), None) => return Err(e.into()),
+            (Err(e), Some(_)) => {
+                report.issues.push(RowIssue {
+                    line,
+                    column: None,
+                    reason: e.to_string(),
+                });
+                report.rows_skipped += 1;
+                continue;
+            }
+        };
+
+        // Sin tiempo valido la fila no se puede ubicar: siempre se descarta
+        let timestamp = match ts_index {
+            Some(i) => {
+                let raw = record.get(i).unwrap_or("").trim();
+                match raw.parse::<u64>() {
+                    Ok(t) => Some(t),
+                    Err(_) => {
+                        let reason = format!("invalid timestamp '{}'", raw);
+                        if options.is_none() {
+                            return Err(Error::Data(format!("Line {}: {}", line, reason)));
+                        }
+                        report.issues.push(RowIssue {
+                            line,
+                            column: schema.timestamp.clone(),
+                            reason,
+                        });
+                        report.rows_skipped += 1;
+                        continue;
+                    }
+                }
+            }
+            None => None,
+        };
+
+        let mut missing = 0;
+        for ((value, &i), name) in row_values.iter_mut().zip(&indices).zip(&schema.columns) {
+            let raw = record.get(i).unwrap_or("").trim();
+            *value = match parse_value(raw, options) {
+                Ok(v) => v,
+                Err(reason) => {
+                    if options.is_none() {
+                        return Err(Error::Data(format!(
+                            "Line {}: {} for {}",
+                            line, reason, name
+                        )));
+                    }
+                    report.issues.push(RowIssue {
+                        line,
+                        column: Some(name.clone()),
+                        reason,
+                    });
+                    missing += 1;
+                    f64::NAN
+                }
+            };
+        }
+
+        if missing > 0 && options.map(|o| o.policy) == Some(BadRowPolicy::Skip) {
+            report.rows_skipped += 1;
+            continue;
+        }
+
+        if let (Some(t), Some(ts)) = (timestamp, timestamps.as_mut()) {
+            ts.push(t);
+        }
+        for (column, &value) in columns.iter_mut().zip(&row_values) {
+            column.push(value);
+        }
+        report.missing_values += missing;
+        report.rows_kept += 1;
     }
 
-    Ok(data_vec)
+    let data = Dataset {
+        timestamps,
+        timestamp_name: schema.timestamp.clone(),
+        names: schema.columns.clone(),
+        columns,
+    };
+    Ok((data, report))
 }
 
 // Funcion para extraer una columna para analisis o lo que sea
-pub fn extract_series(data: &[Data], field: &str) -> Vec<f64> {
-    match field {
-        "Temperature" => data.iter().map(|d| d.Temperature).collect(),
-        "Humidity" => data.iter().map(|d| d.Humidity).collect(),
-        _ => panic!("Unsupported field: {}", field),
+pub fn extract_series(data: &Dataset, field: &str) -> Result<Vec<f64>> {
+    if let Some(column) = data.column(field) {
+        return Ok(column.to_vec());
+    }
+
+    // La columna de tiempo tambien se puede extraer como serie
+    match (&data.timestamp_name, &data.timestamps) {
+        (Some(name), Some(ts)) if name == field => Ok(ts.iter().map(|&t| t as f64).collect()),
+        _ => Err(Error::Data(format!("Unsupported field: {}", field))),
     }
 }
+
+#[test]
+fn test_read_csv_discovers_columns() {
+    let data = read_csv("sensor_data.csv").unwrap();
+    assert_eq!(data.columns(), ["Temperature", "Humidity"]);
+    assert_eq!(data.timestamps().unwrap().len(), data.len());
+    assert_eq!(extract_series(&data, "Humidity").unwrap().len(), data.len());
+    assert!(extract_series(&data, "CO2").is_err());
+
+    let path = std::env::temp_dir().join("embebidos_duplicate_header_test.csv");
+    std::fs::write(&path, "Timestamp,Temperature,Temperature\n0,27.6,27.7\n").unwrap();
+    let err = read_csv(path.to_str().unwrap()).unwrap_err();
+    assert!(err.to_string().contains("Duplicated column: Temperature"));
+}
+
+#[test]
+fn test_read_csv_lenient_reports_bad_rows() {
+    let path = std::env::temp_dir().join("embebidos_lenient_test.csv");
+    std::fs::write(
+        &path,
+        "Timestamp,Temperature,Humidity\n\
+         0,27.6,71.0\n\
+         10000,nan,71.0\n\
+         20000,-999,70.5\n\
+         30000,27.7\n\
+         abc,27.7,70.0\n\
+         50000,27.8,70.1\n",
+    )
+    .unwrap();
+    let path = path.to_str().unwrap();
+    assert!(read_csv(path).is_err());
+
+    let (data, report) = read_csv_lenient(path, &ReadOptions::default()).unwrap();
+    assert_eq!(data.len(), 5);
+    assert_eq!(data.missing_count("Temperature"), Some(2));
+    assert_eq!(data.missing_count("Humidity"), Some(1));
+    assert_eq!(report.rows_skipped, 1);
+    assert_eq!(report.issues.len(), 4);
+    assert_eq!(report.issues[3].line, 6);
+
+    let skip = ReadOptions {
+        policy: BadRowPolicy::Skip,
+        ..ReadOptions::default()
+    };
+    let (data, report) = read_csv_lenient(path, &skip).unwrap();
+    assert_eq!(data.len(), 2);
+    assert_eq!(report.rows_kept, 2);
+
+    // La lectura estricta acepta nan; las lineas cuentan los saltos dentro de comillas
+    let path = std::env::temp_dir().join("embebidos_strict_test.csv");
+    std::fs::write(
+        &path,
+        "Timestamp,Temperature,Note\n\
+         0,nan,\"dos\nlineas\"\n\
+         10000,27.6,ok\n\
+         20000,x,ok\n",
+    )
+    .unwrap();
+    let path = path.to_str().unwrap();
+    let schema = Schema::new(Some("Timestamp"), &["Temperature"]);
+    let err = read_csv_with_schema(path, &schema).unwrap_err().to_string();
+    assert!(err.contains("Line 5"), "{}", err);
+    let (data, _) = read_csv_lenient_with_schema(path, &schema, &ReadOptions::default()).unwrap();
+    assert_eq!(data.missing_count("Temperature"), Some(2));
+
+    std::fs::write(path, "Timestamp,Temperature\n0,nan\n10000,inf\n").unwrap();
+    let data = read_csv(path).unwrap();
+    assert!(data.column("Temperature").unwrap()[0].is_nan());
+}
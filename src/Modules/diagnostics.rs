@@ -0,0 +1,272 @@
+use super::arima::ArimaModel;
+use super::error::{Error, Result};
+use super::stats::{acf, ols, pacf, white_noise_band};
+use nalgebra::{DMatrix, DVector};
+use statrs::distribution::{ChiSquared, ContinuousCDF};
+
+/// Resultado de una prueba con distribucion chi-cuadrado bajo H0
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChiSquaredTest {
+    pub statistic: f64,
+    pub dof: usize,
+    pub p_value: f64,
+}
+
+impl ChiSquaredTest {
+    fn new(statistic: f64, dof: usize) -> Result<Self> {
+        let dist = ChiSquared::new(dof as f64).map_err(|e| Error::Evaluation(e.to_string()))?;
+        Ok(ChiSquaredTest {
+            statistic,
+            dof,
+            p_value: 1.0 - dist.cdf(statistic),
+        })
+    }
+
+    /// Indica si la hipotesis nula se rechaza al nivel `alpha`
+    pub fn rejects_at(&self, alpha: f64) -> bool {
+        self.p_value <= alpha
+    }
+}
+
+/// Resumen descriptivo de los residuos
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResidualSummary {
+    pub n: usize,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub skewness: f64,
+    pub excess_kurtosis: f64, // 0 para una normal
+    pub min: f64,
+    pub max: f64,
+}
+
+/// Resume los residuos (momentos con divisor n)
+pub fn residual_summary(residuals: &[f64]) -> Result<ResidualSummary> {
+    let n = residuals.len();
+    if n < 2 {
+        return Err(Error::Data(
+            "At least two residuals are needed for a summary".into(),
+        ));
+    }
+    let nf = n as f64;
+    let mean = residuals.iter().sum::<f64>() / nf;
+    let moment = |k: i32| residuals.iter().map(|e| (e - mean).powi(k)).sum::<f64>() / nf;
+    let m2 = moment(2);
+    if m2 <= 0.0 {
+        return Err(Error::Data("Residuals are constant".into()));
+    }
+    Ok(ResidualSummary {
+        n,
+        mean,
+        std_dev: m2.sqrt(),
+        skewness: moment(3) / m2.powf(1.5),
+        excess_kurtosis: moment(4) / (m2 * m2) - 3.0,
+        min: residuals.iter().copied().fold(f64::INFINITY, f64::min),
+        max: residuals.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+    })
+}
+
+// Grados de libertad de las pruebas portmanteau tras ajustar `fitted` parametros
+fn portmanteau_dof(lags: usize, fitted: usize) -> Result<usize> {
+    if lags <= fitted {
+        return Err(Error::Evaluation(format!(
+            "Need more lags ({}) than fitted ARMA parameters ({})",
+            lags, fitted
+        )));
+    }
+    Ok(lags - fitted)
+}
+
+/// Prueba de Ljung-Box de autocorrelacion conjunta hasta `lags`
+///
+/// H0: los residuos no estan autocorrelacionados. `fitted` es el numero de
+/// parametros ARMA estimados, que se restan a los grados de libertad.
+pub fn ljung_box(residuals: &[f64], lags: usize, fitted: usize) -> Result<ChiSquaredTest> {
+    let dof = portmanteau_dof(lags, fitted)?;
+    let n = residuals.len() as f64;
+    let rho = acf(residuals, lags)?;
+    let statistic = n
+        * (n + 2.0)
+        * rho
+            .iter()
+            .enumerate()
+            .map(|(k, r)| r * r / (n - k as f64 - 1.0))
+            .sum::<f64>();
+    ChiSquaredTest::new(statistic, dof)
+}
+
+/// Prueba de Box-Pierce (version sin correccion de muestra de Ljung-Box)
+pub fn box_pierce(residuals: &[f64], lags: usize, fitted: usize) -> Result<ChiSquaredTest> {
+    let dof = portmanteau_dof(lags, fitted)?;
+    let n = residuals.len() as f64;
+    let statistic = n * acf(residuals, lags)?.iter().map(|r| r * r).sum::<f64>();
+    ChiSquaredTest::new(statistic, dof)
+}
+
+/// Prueba de normalidad de Jarque-Bera; H0: asimetria y exceso de curtosis nulos
+pub fn jarque_bera(residuals: &[f64]) -> Result<ChiSquaredTest> {
+    let summary = residual_summary(residuals)?;
+    let n = summary.n as f64;
+    let statistic = n / 6.0 * (summary.skewness.powi(2) + summary.excess_kurtosis.powi(2) / 4.0);
+    ChiSquaredTest::new(statistic, 2)
+}
+
+/// Prueba ARCH-LM de Engle: regresa e_t^2 sobre sus `lags` rezagos
+///
+/// H0: varianza constante. El estadistico es n R^2 ~ chi2(lags).
+pub fn arch_lm(residuals: &[f64], lags: usize) -> Result<ChiSquaredTest> {
+    if lags == 0 || residuals.len() < 2 * lags + 10 {
+        return Err(Error::Data(
+            "Not enough residuals for the ARCH-LM test".into(),
+        ));
+    }
+    let squared: Vec<f64> = residuals.iter().map(|e| e * e).collect();
+    let rows = squared.len() - lags;
+
+    // e_t^2 = a + sum b_j e_{t-j}^2
+    let mut x = DMatrix::zeros(rows, lags + 1);
+    let mut y = DVector::zeros(rows);
+    for r in 0..rows {
+        let t = r + lags;
+        y[r] = squared[t];
+        x[(r, 0)] = 1.0;
+        for j in 1..=lags {
+            x[(r, j)] = squared[t - j];
+        }
+    }
+    let (beta, _) = ols(&x, &y).ok_or_else(|| {
+        Error::Evaluation("ARCH-LM regression is singular (constant residuals?)".into())
+    })?;
+
+    let mean = y.mean();
+    let total: f64 = y.iter().map(|v| (v - mean).powi(2)).sum();
+    let residual = (&y - &x * &beta).norm_squared();
+    let r_squared = if total > 0.0 {
+        1.0 - residual / total
+    } else {
+        0.0
+    };
+    ChiSquaredTest::new(rows as f64 * r_squared, lags)
+}
+
+/// Opciones de `diagnose`
+#[derive(Debug, Clone)]
+pub struct DiagnosticsOptions {
+    pub lags: Option<usize>, // Rezagos de ACF/PACF y Ljung-Box; por defecto min(10, n / 5)
+    pub fitted: usize,       // Parametros ARMA estimados
+    pub arch_lags: usize,
+    pub level: f64, // Confianza de la banda de la ACF, p. ej. 0.95
+}
+
+impl Default for DiagnosticsOptions {
+    fn default() -> Self {
+        DiagnosticsOptions {
+            lags: None,
+            fitted: 0,
+            arch_lags: 5,
+            level: 0.95,
+        }
+    }
+}
+
+/// Conjunto completo de diagnosticos de residuos
+#[derive(Debug, Clone)]
+pub struct Diagnostics {
+    pub summary: ResidualSummary,
+    pub acf: Vec<f64>,  // Rezagos 1..=lags
+    pub pacf: Vec<f64>, // Rezagos 1..=lags
+    pub acf_band: f64,  // Banda de ruido blanco al nivel de las opciones: z / sqrt(n)
+    pub ljung_box: ChiSquaredTest,
+    pub box_pierce: ChiSquaredTest,
+    pub jarque_bera: ChiSquaredTest,
+    pub arch_lm: ChiSquaredTest,
+}
+
+impl Diagnostics {
+    /// El modelo es adecuado si los residuos no tienen autocorrelacion ni
+    /// heterocedasticidad condicional al nivel `alpha`. La normalidad no se
+    /// exige porque solo afecta a los intervalos, no a los pronosticos.
+    pub fn is_adequate(&self, alpha: f64) -> bool {
+        !self.ljung_box.rejects_at(alpha) && !self.arch_lm.rejects_at(alpha)
+    }
+}
+
+/// Calcula todos los diagnosticos sobre una serie de residuos
+pub fn diagnose(residuals: &[f64], options: &DiagnosticsOptions) -> Result<Diagnostics> {
+    let n = residuals.len();
+    let lags = options
+        .lags
+        .unwrap_or_else(|| 10.min(n / 5).max(options.fitted + 1));
+    Ok(Diagnostics {
+        summary: residual_summary(residuals)?,
+        acf: acf(residuals, lags)?,
+        pacf: pacf(residuals, lags)?,
+        acf_band: white_noise_band(n, options.level)?,
+        ljung_box: ljung_box(residuals, lags, options.fitted)?,
+        box_pierce: box_pierce(residuals, lags, options.fitted)?,
+        jarque_bera: jarque_bera(residuals)?,
+        arch_lm: arch_lm(residuals, options.arch_lags)?,
+    })
+}
+
+impl ArimaModel {
+    /// Diagnosticos de los residuos del ajuste
+    ///
+    /// Para modelos estacionales usa 2 periodos de rezagos (Hyndman y
+    /// Athanasopoulos); si no, min(10, n / 5).
+    pub fn diagnostics(&self) -> Result<Diagnostics> {
+        let spec = self.spec();
+        let fitted = spec.p + spec.q + spec.seasonal.map_or(0, |s| s.p + s.q);
+        let n = self.residuals().len();
+        let lags = match spec.seasonal {
+            Some(s) => (2 * s.period).min(n / 5),
+            None => 10.min(n / 5),
+        }
+        .max(fitted + 1);
+        diagnose(
+            self.residuals(),
+            &DiagnosticsOptions {
+                lags: Some(lags),
+                fitted,
+                ..DiagnosticsOptions::default()
+            },
+        )
+    }
+}
+
+#[test]
+fn test_diagnostics_flag_misspecified_model() {
+    use super::test_util::Lcg;
+    let mut rng = Lcg::new(31);
+    let mut value = 0.0;
+    let series: Vec<f64> = (0..500)
+        .map(|_| {
+            value = 0.8 * value + rng.noise();
+            value
+        })
+        .collect();
+
+    // Un AR(1) deja ruido blanco; la media sola deja la autocorrelacion
+    let good = ArimaModel::fit_series(&series, 1, 0, 0).unwrap();
+    let report = good.diagnostics().unwrap();
+    assert!(report.is_adequate(0.05));
+    assert!(report.acf.iter().all(|r| r.abs() < 3.0 * report.acf_band));
+    assert!((report.acf_band - 1.96 / 500f64.sqrt()).abs() < 1e-4);
+    let wide = diagnose(
+        good.residuals(),
+        &DiagnosticsOptions {
+            level: 0.99,
+            ..DiagnosticsOptions::default()
+        },
+    )
+    .unwrap();
+    assert!((wide.acf_band - 2.5758 / 500f64.sqrt()).abs() < 1e-4);
+
+    let bad = ArimaModel::fit_series(&series, 0, 0, 0).unwrap();
+    let report = bad.diagnostics().unwrap();
+    assert!(report.ljung_box.rejects_at(0.01));
+    assert!((report.pacf[0] - report.acf[0]).abs() < 1e-12);
+    assert!((report.pacf[0] - 0.8).abs() < 0.1);
+    // Ruido uniforme: colas mas livianas que la normal
+    assert!(report.summary.excess_kurtosis < 0.0);
+}
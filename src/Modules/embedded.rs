@@ -0,0 +1,272 @@
+//! Nucleo numerico de ARIMA sin `std` ni memoria dinamica
+//!
+//! Diferenciacion, recursion AR/MA y pronostico sobre arreglos de tamano fijo
+//! (const generics), para compilar el mismo codigo en microcontroladores
+//! (`thumbv7em-none-eabihf`) con `--no-default-features`. El modelo de
+//! `arima.rs` comparte la diferenciacion, los coeficientes de integracion y
+//! los residuos CSS. `ArimaPredictor` es una copia aparte de la recursion de
+//! `ForecastOrigin` con el mismo orden de operaciones; un test verifica que
+//! sus pronosticos coinciden bit a bit con los del host.
+
+/// Diferencia `order` veces la serie en el mismo buffer
+///
+/// Returns:
+/// - La longitud de la serie diferenciada, guardada en `values[..len]`
+pub fn difference_in_place(values: &mut [f64], order: usize) -> usize {
+    let mut len = values.len();
+    for _ in 0..order {
+        if len == 0 {
+            return 0;
+        }
+        for i in 0..len - 1 {
+            values[i] = values[i + 1] - values[i];
+        }
+        len -= 1;
+    }
+    len
+}
+
+/// Diferencia estacional (1 - B^s)^D en el mismo buffer
+pub fn seasonal_difference_in_place(values: &mut [f64], period: usize, order: usize) -> usize {
+    let mut len = values.len();
+    for _ in 0..order {
+        if len <= period {
+            return 0;
+        }
+        for i in 0..len - period {
+            values[i] = values[i + period] - values[i];
+        }
+        len -= period;
+    }
+    len
+}
+
+/// Coeficientes delta_k de (1 - B)^d (1 - B^s)^D = 1 - sum delta_k B^k
+///
+/// `L` debe ser `d + seasonal_d * period`; si no coincide devuelve `None`.
+pub fn integration_coeffs<const L: usize>(
+    d: usize,
+    seasonal_d: usize,
+    period: usize,
+) -> Option<[f64; L]> {
+    if d + seasonal_d * period != L {
+        return None;
+    }
+    let mut out = [0.0; L];
+    integration_coeffs_into(d, seasonal_d, period, &mut out);
+    Some(out)
+}
+
+/// Igual que `integration_coeffs` pero escribe en un slice de largo
+/// `d + seasonal_d * period`
+pub fn integration_coeffs_into(d: usize, seasonal_d: usize, period: usize, out: &mut [f64]) {
+    // Coeficientes del polinomio sin el termino independiente (siempre 1):
+    // out[i - 1] es el coeficiente de B^i
+    for c in out.iter_mut() {
+        *c = 0.0;
+    }
+    let mut degree = 0;
+    let lags = core::iter::repeat_n(1, d).chain(core::iter::repeat_n(period, seasonal_d));
+    for lag in lags {
+        // Multiplica por (1 - B^lag) de mayor a menor grado
+        degree += lag;
+        for i in (lag..=degree).rev() {
+            let below = if i == lag { 1.0 } else { out[i - lag - 1] };
+            out[i - 1] -= below;
+        }
+    }
+    for c in out.iter_mut() {
+        *c = -*c;
+    }
+}
+
+/// Residuos condicionales (CSS) de una serie ya diferenciada
+///
+/// Los residuos previos a la muestra valen cero y la recursion empieza
+/// cuando hay `ar.len()` rezagos disponibles; `out[..ar.len()]` queda en cero.
+pub fn css_residuals(series: &[f64], ar: &[f64], ma: &[f64], mean: f64, out: &mut [f64]) {
+    let p = ar.len();
+    let q = ma.len();
+    for r in out[..p.min(series.len())].iter_mut() {
+        *r = 0.0;
+    }
+
+    for t in p..series.len() {
+        let mut predicted = mean;
+        for j in 0..p {
+            predicted += ar[j] * (series[t - j - 1] - mean);
+        }
+        for j in 0..q.min(t) {
+            predicted += ma[j] * out[t - j - 1];
+        }
+        out[t] = series[t] - predicted;
+    }
+}
+
+/// Buffer circular de tamano fijo; `lag(0)` es el valor mas reciente
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ring<T, const N: usize> {
+    data: [T; N],
+    head: usize, // Posicion del valor mas antiguo
+}
+
+impl<T: Copy, const N: usize> Ring<T, N> {
+    /// Crea el buffer con los valores ordenados del mas antiguo al mas reciente
+    pub const fn new(oldest_first: [T; N]) -> Self {
+        Ring {
+            data: oldest_first,
+            head: 0,
+        }
+    }
+
+    pub fn lag(&self, j: usize) -> T {
+        self.data[(self.head + N - 1 - j) % N]
+    }
+
+    pub fn push(&mut self, value: T) {
+        if N > 0 {
+            self.data[self.head] = value;
+            self.head = (self.head + 1) % N;
+        }
+    }
+}
+
+/// Predictor ARIMA con estado acotado: `P` rezagos AR, `Q` rezagos MA y `L`
+/// niveles para deshacer la diferenciacion (regular y estacional)
+///
+/// Los coeficientes son los expandidos (ya multiplicados por la parte
+/// estacional), como los que entrega `ArimaModel::predictor_parts`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArimaPredictor<const P: usize, const Q: usize, const L: usize> {
+    ar: [f64; P],
+    ma: [f64; Q],
+    delta: [f64; L],
+    mean: f64,
+    levels: Ring<f64, L>,
+    values: Ring<f64, P>,
+    residuals: Ring<f64, Q>,
+}
+
+impl<const P: usize, const Q: usize, const L: usize> ArimaPredictor<P, Q, L> {
+    /// Predictor sin historia: niveles en cero, rezagos en la media
+    pub const fn new(ar: [f64; P], ma: [f64; Q], delta: [f64; L], mean: f64) -> Self {
+        ArimaPredictor {
+            ar,
+            ma,
+            delta,
+            mean,
+            levels: Ring::new([0.0; L]),
+            values: Ring::new([mean; P]),
+            residuals: Ring::new([0.0; Q]),
+        }
+    }
+
+    /// Fija el origen del pronostico; cada arreglo va del mas antiguo al mas reciente
+    pub const fn with_state(
+        mut self,
+        levels: [f64; L],
+        values: [f64; P],
+        residuals: [f64; Q],
+    ) -> Self {
+        self.levels = Ring::new(levels);
+        self.values = Ring::new(values);
+        self.residuals = Ring::new(residuals);
+        self
+    }
+
+    // Prediccion a un paso de la serie diferenciada
+    fn predict_value(&self) -> f64 {
+        let mut prediction = self.mean;
+        for (j, coeff) in self.ar.iter().enumerate() {
+            prediction += coeff * (self.values.lag(j) - self.mean);
+        }
+        for (j, coeff) in self.ma.iter().enumerate() {
+            prediction += coeff * self.residuals.lag(j);
+        }
+        prediction
+    }
+
+    // y_t = w_t + sum delta_k y_{t-k}
+    fn integrate(&self, value: f64) -> f64 {
+        let mut level = value;
+        for (k, coeff) in self.delta.iter().enumerate() {
+            level += coeff * self.levels.lag(k);
+        }
+        level
+    }
+
+    /// Pronostico del siguiente paso
+    pub fn predict(&self) -> f64 {
+        self.integrate(self.predict_value())
+    }
+
+    /// Agrega una observacion y devuelve el pronostico del siguiente paso
+    pub fn update(&mut self, observation: f64) -> f64 {
+        let value = observation - self.integrate(0.0);
+        let residual = value - self.predict_value();
+        self.levels.push(observation);
+        self.values.push(value);
+        self.residuals.push(residual);
+        self.predict()
+    }
+
+    /// Pronostico de varios pasos, uno por elemento de `out`
+    pub fn forecast(&self, out: &mut [f64]) {
+        let mut state = *self;
+        for slot in out.iter_mut() {
+            let value = state.predict_value();
+            let level = state.integrate(value);
+            state.levels.push(level);
+            state.values.push(value);
+            state.residuals.push(0.0);
+            *slot = level;
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_fixed_predictor_matches_std_model_bitwise() {
+    use super::arima::{ArimaModel, ArimaSpec, SeasonalOrder};
+
+    let series: Vec<f64> = (0..360)
+        .map(|t| 20.0 + 0.02 * t as f64 + (t as f64 / 2.0).sin() + 0.2 * (t as f64 / 7.0).cos())
+        .collect();
+    let spec = ArimaSpec::new(1, 1, 1).with_seasonal(SeasonalOrder::new(1, 0, 0, 12));
+    let mut model = ArimaModel::fit_spec(&series[..300], &spec).unwrap();
+
+    let parts = model.predictor_parts();
+    let predictor = ArimaPredictor::<13, 1, 1>::new(
+        parts.ar.as_slice().try_into().unwrap(),
+        parts.ma.as_slice().try_into().unwrap(),
+        parts.delta.as_slice().try_into().unwrap(),
+        parts.mean,
+    );
+    let mut predictor = predictor.with_state(
+        parts.levels.as_slice().try_into().unwrap(),
+        parts.values.as_slice().try_into().unwrap(),
+        parts.residuals.as_slice().try_into().unwrap(),
+    );
+
+    let mut out = [0.0; 24];
+    predictor.forecast(&mut out);
+    let bits = |v: &[f64]| v.iter().map(|x| x.to_bits()).collect::<Vec<_>>();
+    assert_eq!(bits(&out), bits(&model.forecast(24)));
+
+    for &y in &series[300..] {
+        assert_eq!(
+            predictor.update(y).to_bits(),
+            model.update(y).unwrap().to_bits()
+        );
+    }
+
+    // Diferenciacion y coeficientes de integracion sin asignar memoria
+    let mut buffer = [1.0, 4.0, 9.0, 16.0, 25.0, 36.0];
+    let len = difference_in_place(&mut buffer, 2);
+    assert_eq!(&buffer[..len], &[2.0, 2.0, 2.0, 2.0]);
+    assert_eq!(integration_coeffs::<2>(2, 0, 0), Some([2.0, -1.0]));
+    assert_eq!(
+        integration_coeffs::<5>(1, 1, 4),
+        Some([1.0, 0.0, 0.0, 1.0, -1.0])
+    );
+}
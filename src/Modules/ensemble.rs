@@ -0,0 +1,515 @@
+use super::arima::{IntervalForecast, RESIDUAL_WINDOW, push_residual, residual_window};
+use super::arima_eval::{WeightingStrategy, generate_weights};
+use super::backtest::{BacktestOptions, BacktestResult, backtest_forecaster};
+use super::error::{Error, Result};
+use super::forecaster::{Forecaster, ForecasterSpec};
+use nalgebra::{DMatrix, DVector};
+use std::f64::consts::PI;
+
+/// Como se eligen los pesos de los modelos del ensamble
+#[derive(Debug, Clone)]
+pub enum Combination {
+    Equal,      // Promedio simple
+    InverseMse, // Proporcionales a 1 / MSE de validacion
+    Stacking,   // Minimos cuadrados con pesos no negativos que suman 1
+    // Pesos de `generate_weights` sobre los modelos ordenados del mayor al
+    // menor MSE: el mejor ocupa la posicion del valor mas reciente. Con
+    // `InverseDistance` las distancias siguen ese mismo orden.
+    Strategy(WeightingStrategy),
+}
+
+/// Especificacion ajustable guardada en una caja, para mezclar familias de
+/// modelos distintas en un mismo ensamble
+pub trait DynForecasterSpec {
+    fn fit_boxed(&self, series: &[f64]) -> Result<Box<dyn Forecaster>>;
+}
+
+impl<S> DynForecasterSpec for S
+where
+    S: ForecasterSpec,
+    S::Model: 'static,
+{
+    fn fit_boxed(&self, series: &[f64]) -> Result<Box<dyn Forecaster>> {
+        Ok(Box::new(self.fit(series)?))
+    }
+}
+
+/// Precision de validacion y peso asignado a un miembro
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemberScore {
+    pub name: String,
+    pub mse: f64, // Sobre todos los origenes y horizontes del backtest
+    pub weight: f64,
+}
+
+/// Combinacion lineal de pronosticadores ajustados
+///
+/// Los pesos suman 1, asi que el residuo del ensamble es la combinacion de
+/// los residuos de los miembros (alineados por el final). Como en los
+/// miembros, la verosimilitud y `nobs` quedan fijos al construirlo y
+/// `update` solo agrega residuos.
+pub struct Ensemble {
+    members: Vec<Box<dyn Forecaster>>,
+    weights: Vec<f64>,
+    validation: Vec<MemberScore>,
+    residuals: Vec<f64>,
+    log_likelihood: f64,
+    nobs: usize,
+    updates: usize,
+}
+
+impl Ensemble {
+    /// Construye un ensamble con pesos fijos (se normalizan para sumar 1)
+    pub fn new(members: Vec<Box<dyn Forecaster>>, weights: Vec<f64>) -> Result<Self> {
+        if members.is_empty() {
+            return Err(Error::Model("An ensemble needs at least one model".into()));
+        }
+        if weights.len() != members.len() {
+            return Err(Error::Model(format!(
+                "Expected {} weights, got {}",
+                members.len(),
+                weights.len()
+            )));
+        }
+        if weights.iter().any(|w| !w.is_finite() || *w < 0.0) {
+            return Err(Error::Model(
+                "Ensemble weights must be finite and non-negative".into(),
+            ));
+        }
+        let total: f64 = weights.iter().sum();
+        if total <= 0.0 {
+            return Err(Error::Model("Ensemble weights must not all be zero".into()));
+        }
+        let weights: Vec<f64> = weights.iter().map(|w| w / total).collect();
+
+        let len = members
+            .iter()
+            .map(|m| m.residuals().len())
+            .min()
+            .unwrap_or(0);
+        let residuals: Vec<f64> = (0..len)
+            .map(|t| {
+                members
+                    .iter()
+                    .zip(&weights)
+                    .map(|(m, w)| {
+                        let own = m.residuals();
+                        w * own[own.len() - len + t]
+                    })
+                    .sum()
+            })
+            .collect();
+        if len == 0 {
+            return Err(Error::Model(
+                "Ensemble members have no in-sample residuals to combine".into(),
+            ));
+        }
+        let sigma2 = residuals.iter().map(|e| e * e).sum::<f64>() / len as f64;
+        let log_likelihood =
+            -0.5 * len as f64 * ((2.0 * PI * sigma2.max(f64::MIN_POSITIVE)).ln() + 1.0);
+
+        Ok(Ensemble {
+            members,
+            weights,
+            validation: Vec::new(),
+            residuals,
+            log_likelihood,
+            nobs: len,
+            updates: 0,
+        })
+    }
+
+    /// Pesos normalizados, en el orden de los miembros
+    pub fn weights(&self) -> &[f64] {
+        &self.weights
+    }
+
+    pub fn members(&self) -> &[Box<dyn Forecaster>] {
+        &self.members
+    }
+
+    /// Puntajes del backtest de validacion (vacio si los pesos fueron fijos)
+    pub fn validation(&self) -> &[MemberScore] {
+        &self.validation
+    }
+
+    /// Observaciones incorporadas con `update` desde la construccion
+    pub fn updates(&self) -> usize {
+        self.updates
+    }
+
+    fn combine(&self, forecasts: &[Vec<f64>]) -> Vec<f64> {
+        let steps = forecasts.first().map_or(0, |f| f.len());
+        (0..steps)
+            .map(|h| {
+                forecasts
+                    .iter()
+                    .zip(&self.weights)
+                    .map(|(f, w)| w * f[h])
+                    .sum()
+            })
+            .collect()
+    }
+}
+
+impl Forecaster for Ensemble {
+    fn name(&self) -> String {
+        let parts: Vec<String> = self
+            .members
+            .iter()
+            .zip(&self.weights)
+            .map(|(m, w)| format!("{:.2}*{}", w, m.name()))
+            .collect();
+        format!("Ensemble[{}]", parts.join(" + "))
+    }
+
+    fn forecast(&self, steps: usize) -> Vec<f64> {
+        let forecasts: Vec<Vec<f64>> = self.members.iter().map(|m| m.forecast(steps)).collect();
+        self.combine(&forecasts)
+    }
+
+    /// El error estandar es la suma ponderada de los de cada miembro, la
+    /// cota que corresponde a errores perfectamente correlacionados; los
+    /// intervalos quedan conservadores.
+    fn forecast_with_intervals(&self, steps: usize, levels: &[f64]) -> Result<IntervalForecast> {
+        let parts = self
+            .members
+            .iter()
+            .map(|m| m.forecast_with_intervals(steps, levels))
+            .collect::<Result<Vec<_>>>()?;
+        let means: Vec<Vec<f64>> = parts.iter().map(|p| p.mean.clone()).collect();
+        let errors: Vec<Vec<f64>> = parts.iter().map(|p| p.std_error.clone()).collect();
+        IntervalForecast::gaussian(self.combine(&means), self.combine(&errors), levels)
+    }
+
+    /// Valida la observacion en todos los miembros antes de tocar alguno,
+    /// asi un rechazo deja el ensamble sin cambios
+    fn update(&mut self, observation: f64) -> Result<f64> {
+        self.check_observation(observation)?;
+        let next = self
+            .members
+            .iter_mut()
+            .map(|m| m.update(observation))
+            .collect::<Result<Vec<f64>>>()?;
+        // Cada miembro agrego su error a un paso; el del ensamble es su combinacion
+        let residual = self
+            .members
+            .iter()
+            .zip(&self.weights)
+            .map(|(m, w)| w * m.residuals().last().copied().unwrap_or(0.0))
+            .sum();
+        let capacity = self.nobs.max(RESIDUAL_WINDOW);
+        push_residual(&mut self.residuals, residual, capacity);
+        self.updates += 1;
+        Ok(next.iter().zip(&self.weights).map(|(f, w)| w * f).sum())
+    }
+
+    fn check_observation(&self, observation: f64) -> Result<()> {
+        self.members
+            .iter()
+            .try_for_each(|m| m.check_observation(observation))
+    }
+
+    fn residuals(&self) -> &[f64] {
+        residual_window(&self.residuals, self.nobs.max(RESIDUAL_WINDOW))
+    }
+
+    /// Verosimilitud gaussiana de los residuos combinados al construirlo
+    fn log_likelihood(&self) -> f64 {
+        self.log_likelihood
+    }
+
+    fn nobs(&self) -> usize {
+        self.nobs
+    }
+
+    // Parametros de los miembros mas los pesos libres
+    fn n_params(&self) -> usize {
+        self.members.iter().map(|m| m.n_params()).sum::<usize>() + self.members.len() - 1
+    }
+}
+
+/// Especificacion de un ensamble: miembros, combinacion y el backtest con
+/// que se estiman los pesos
+pub struct EnsembleSpec {
+    pub members: Vec<Box<dyn DynForecasterSpec>>,
+    pub combination: Combination,
+    pub validation: BacktestOptions,
+}
+
+impl EnsembleSpec {
+    pub fn new(combination: Combination, validation: BacktestOptions) -> Self {
+        EnsembleSpec {
+            members: Vec::new(),
+            combination,
+            validation,
+        }
+    }
+
+    /// Agrega un miembro de cualquier familia (ARIMA, ETS, otro ensamble...)
+    pub fn with_member<S: DynForecasterSpec + 'static>(mut self, spec: S) -> Self {
+        self.members.push(Box::new(spec));
+        self
+    }
+}
+
+impl ForecasterSpec for EnsembleSpec {
+    type Model = Ensemble;
+
+    /// Estima los pesos con un backtest de origen rodante de cada miembro y
+    /// luego ajusta todos los miembros con la serie completa
+    fn fit(&self, series: &[f64]) -> Result<Ensemble> {
+        if self.members.is_empty() {
+            return Err(Error::Model("An ensemble needs at least one model".into()));
+        }
+        let backtests = self
+            .members
+            .iter()
+            .map(|m| backtest_forecaster(series, &self.validation, |train| m.fit_boxed(train)))
+            .collect::<Result<Vec<BacktestResult>>>()?;
+
+        // Pronosticos de todos los origenes y horizontes, en el mismo orden
+        let actual: Vec<f64> = backtests[0]
+            .records
+            .iter()
+            .flat_map(|r| r.actual.iter().copied())
+            .collect();
+        let forecasts: Vec<Vec<f64>> = backtests
+            .iter()
+            .map(|b| {
+                b.records
+                    .iter()
+                    .flat_map(|r| r.forecast.iter().copied())
+                    .collect()
+            })
+            .collect();
+        let mse: Vec<f64> = forecasts
+            .iter()
+            .map(|f| {
+                f.iter()
+                    .zip(&actual)
+                    .map(|(p, y)| (y - p).powi(2))
+                    .sum::<f64>()
+                    / actual.len() as f64
+            })
+            .collect();
+
+        let weights = match &self.combination {
+            Combination::Equal => vec![1.0; mse.len()],
+            Combination::InverseMse => inverse_mse_weights(&mse),
+            Combination::Stacking => stacking_weights(&forecasts, &actual)?,
+            Combination::Strategy(strategy) => rank_weights(&mse, strategy.clone())?,
+        };
+
+        let members = self
+            .members
+            .iter()
+            .map(|m| m.fit_boxed(series))
+            .collect::<Result<Vec<_>>>()?;
+        let mut ensemble = Ensemble::new(members, weights)?;
+        ensemble.validation = ensemble
+            .members
+            .iter()
+            .zip(&mse)
+            .zip(&ensemble.weights)
+            .map(|((m, mse), w)| MemberScore {
+                name: m.name(),
+                mse: *mse,
+                weight: *w,
+            })
+            .collect();
+        Ok(ensemble)
+    }
+}
+
+/// Pesos proporcionales a 1 / MSE; un modelo con MSE nulo se lleva todo el peso
+pub fn inverse_mse_weights(mse: &[f64]) -> Vec<f64> {
+    if mse.iter().any(|m| *m <= 0.0) {
+        return mse.iter().map(|m| (*m <= 0.0) as u8 as f64).collect();
+    }
+    let inverse: Vec<f64> = mse.iter().map(|m| 1.0 / m).collect();
+    let total: f64 = inverse.iter().sum();
+    inverse.iter().map(|v| v / total).collect()
+}
+
+// Pesos de una estrategia asignados por ranking: posicion 0 = mayor MSE
+fn rank_weights(mse: &[f64], strategy: WeightingStrategy) -> Result<Vec<f64>> {
+    let mut order: Vec<usize> = (0..mse.len()).collect();
+    order.sort_by(|a, b| mse[*b].total_cmp(&mse[*a]));
+    let ranked = generate_weights(mse.len(), strategy)?;
+    let mut weights = vec![0.0; mse.len()];
+    for (position, &model) in order.iter().enumerate() {
+        weights[model] = ranked[position];
+    }
+    Ok(weights)
+}
+
+/// Stacking: minimiza ||y - F w||^2 con w >= 0 y sum w = 1
+///
+/// Usa gradiente proyectado sobre el simplex (Duchi et al., 2008) con paso
+/// 1 / L, donde L es el mayor valor propio de F'F.
+///
+/// Parameters:
+/// - forecasts: una serie de pronosticos por modelo, todas del largo de `actual`
+/// - actual: valores observados
+pub fn stacking_weights(forecasts: &[Vec<f64>], actual: &[f64]) -> Result<Vec<f64>> {
+    let m = forecasts.len();
+    let n = actual.len();
+    if m == 0 || n == 0 {
+        return Err(Error::Evaluation(
+            "Stacking needs at least one model and one observation".into(),
+        ));
+    }
+    if forecasts.iter().any(|f| f.len() != n) {
+        return Err(Error::Evaluation(
+            "Every forecast series must have the same length as the actual values".into(),
+        ));
+    }
+
+    let f = DMatrix::from_fn(n, m, |t, i| forecasts[i][t]);
+    let y = DVector::from_column_slice(actual);
+    let gram = f.transpose() * &f;
+    let target = f.transpose() * y;
+    let lipschitz = gram.clone().symmetric_eigen().eigenvalues.max();
+    if !lipschitz.is_finite() || lipschitz <= 0.0 {
+        return Err(Error::Evaluation(
+            "Forecasts are all zero or not finite".into(),
+        ));
+    }
+
+    let mut w = DVector::from_element(m, 1.0 / m as f64);
+    for _ in 0..10_000 {
+        let gradient = &gram * &w - &target;
+        let next = project_simplex(&(&w - gradient / lipschitz));
+        let change = (&next - &w).amax();
+        w = next;
+        if change < 1e-12 {
+            break;
+        }
+    }
+    Ok(w.iter().copied().collect())
+}
+
+// Proyeccion euclidiana sobre { w >= 0, sum w = 1 }
+fn project_simplex(v: &DVector<f64>) -> DVector<f64> {
+    let mut sorted: Vec<f64> = v.iter().copied().collect();
+    sorted.sort_by(|a, b| b.total_cmp(a));
+    let mut cumulative = 0.0;
+    let mut theta = 0.0;
+    for (k, u) in sorted.iter().enumerate() {
+        cumulative += u;
+        let candidate = (cumulative - 1.0) / (k + 1) as f64;
+        if u - candidate > 0.0 {
+            theta = candidate;
+        }
+    }
+    v.map(|x| (x - theta).max(0.0))
+}
+
+#[test]
+fn test_ensemble_weights_favour_accurate_models() {
+    use super::arima::ArimaSpec;
+    use super::ets::EtsSpec;
+    use super::test_util::Lcg;
+
+    // Una combinacion exacta se recupera con stacking
+    let a = vec![1.0, 2.0, 3.0, 4.0, 2.0];
+    let b = vec![2.0, 0.0, 1.0, 5.0, 3.0];
+    let c = vec![0.0, 1.0, 0.0, 1.0, 0.0];
+    let y: Vec<f64> = a.iter().zip(&b).map(|(a, b)| 0.3 * a + 0.7 * b).collect();
+    let w = stacking_weights(&[a, b, c], &y).unwrap();
+    assert!((w[0] - 0.3).abs() < 1e-6 && (w[1] - 0.7).abs() < 1e-6 && w[2].abs() < 1e-9);
+
+    // Tendencia con estacionalidad de periodo 12: Holt-Winters deberia dominar
+    let mut rng = Lcg::new(17);
+    let series: Vec<f64> = (0..168)
+        .map(|t| {
+            let noise = rng.noise();
+            20.0 + 0.1 * t as f64 + 3.0 * (2.0 * PI * t as f64 / 12.0).sin() + noise
+        })
+        .collect();
+    let validation = BacktestOptions {
+        initial_train: 96,
+        horizon: 6,
+        step: 6,
+        refit_every: 100,
+        ..BacktestOptions::default()
+    };
+    let spec = |combination| {
+        EnsembleSpec::new(combination, validation.clone())
+            .with_member(ArimaSpec::new(1, 1, 0))
+            .with_member(EtsSpec::holt_winters(12, false))
+            .with_member(EtsSpec::simple())
+    };
+    let train = &series[..144];
+
+    let equal = spec(Combination::Equal).fit(train).unwrap();
+    assert!(
+        equal
+            .weights()
+            .iter()
+            .all(|w| (w - 1.0 / 3.0).abs() < 1e-12)
+    );
+    let scores = equal.validation();
+    assert!(scores[1].mse < scores[0].mse && scores[1].mse < scores[2].mse);
+
+    let inverse = spec(Combination::InverseMse).fit(train).unwrap();
+    assert!(inverse.weights()[1] > 0.5);
+    let ranked = spec(Combination::Strategy(WeightingStrategy::Linear))
+        .fit(train)
+        .unwrap();
+    assert!((ranked.weights()[1] - 0.5).abs() < 1e-12);
+
+    let stacked = spec(Combination::Stacking).fit(train).unwrap();
+    let total: f64 = stacked.weights().iter().sum();
+    assert!((total - 1.0).abs() < 1e-9 && stacked.weights().iter().all(|w| *w >= 0.0));
+    assert!(stacked.weights()[1] > 0.7);
+    assert!(stacked.name().starts_with("Ensemble["));
+    let bands = stacked.forecast_with_intervals(12, &[0.95]).unwrap();
+    assert_eq!(bands.mean, stacked.forecast(12));
+
+    // Actualizar agrega un residuo sin tocar la verosimilitud
+    let mut updated = spec(Combination::Equal).fit(train).unwrap();
+    let (nobs, residuals, bic) = (updated.nobs(), updated.residuals().len(), updated.bic());
+    let expected = series[144] - updated.forecast(1)[0];
+    updated.update(series[144]).unwrap();
+    assert_eq!((updated.nobs(), updated.bic()), (nobs, bic));
+    assert_eq!(updated.residuals().len(), residuals + 1);
+    assert!((updated.residuals()[residuals] - expected).abs() < 1e-9);
+
+    // Un valor que rechaza algun miembro no altera a ninguno
+    let members: Vec<Box<dyn Forecaster>> = vec![
+        Box::new(ArimaSpec::new(1, 1, 0).fit(train).unwrap()),
+        Box::new(EtsSpec::holt_winters(12, true).fit(train).unwrap()),
+    ];
+    let mut mixed = Ensemble::new(members, vec![0.5, 0.5]).unwrap();
+    let before = (mixed.forecast(12), mixed.residuals().to_vec());
+    let lengths: Vec<usize> = mixed.members.iter().map(|m| m.residuals().len()).collect();
+    assert!(matches!(mixed.update(-1.0), Err(Error::Data(_))));
+    assert_eq!((mixed.forecast(12), mixed.residuals().to_vec()), before);
+    assert!(
+        mixed
+            .members
+            .iter()
+            .zip(&lengths)
+            .all(|(m, n)| m.residuals().len() == *n)
+    );
+    assert_eq!(mixed.updates(), 0);
+
+    // El ensamble es un Forecaster mas: se evalua con el mismo backtest
+    let outer = BacktestOptions {
+        initial_train: 144,
+        horizon: 12,
+        step: 12,
+        refit_every: 10,
+        ..BacktestOptions::default()
+    };
+    let ensemble =
+        backtest_forecaster(&series, &outer, |t| spec(Combination::Stacking).fit(t)).unwrap();
+    let naive = backtest_forecaster(&series, &outer, |t| EtsSpec::simple().fit(t)).unwrap();
+    let rmse = |r: &BacktestResult| {
+        let errors: Vec<f64> = (1..=12).flat_map(|h| r.errors_at(h)).collect();
+        (errors.iter().map(|e| e * e).sum::<f64>() / errors.len() as f64).sqrt()
+    };
+    assert!(rmse(&ensemble) < 0.5 * rmse(&naive));
+}
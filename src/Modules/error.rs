@@ -0,0 +1,70 @@
+use std::fmt;
+
+/// Error comun de todas las APIs publicas del crate
+#[derive(Debug)]
+pub enum Error {
+    Data(String),          // Datos de entrada: columnas, largos, faltantes, series cortas
+    Model(String),         // Especificacion o uso de un modelo
+    Evaluation(String),    // Metricas, comparaciones y backtests
+    Optimization(String),  // El ajuste numerico no produjo un resultado valido
+    Serialization(String), // Formato guardado invalido o incompatible
+    Io(std::io::Error),
+    Csv(csv::Error),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Data(msg) => write!(f, "Data error: {}", msg),
+            Error::Model(msg) => write!(f, "Model error: {}", msg),
+            Error::Evaluation(msg) => write!(f, "Evaluation error: {}", msg),
+            Error::Optimization(msg) => write!(f, "Optimization error: {}", msg),
+            Error::Serialization(msg) => write!(f, "Serialization error: {}", msg),
+            Error::Io(e) => write!(f, "I/O error: {}", e),
+            Error::Csv(e) => write!(f, "CSV error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Csv(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<csv::Error> for Error {
+    fn from(e: csv::Error) -> Self {
+        Error::Csv(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Serialization(e.to_string())
+    }
+}
+
+impl From<postcard::Error> for Error {
+    fn from(e: postcard::Error) -> Self {
+        Error::Serialization(e.to_string())
+    }
+}
+
+// Solo aparece al escribir codigo generado en un `String`, que no falla
+impl From<fmt::Error> for Error {
+    fn from(_: fmt::Error) -> Self {
+        Error::Model("Failed to format generated code".into())
+    }
+}
@@ -0,0 +1,585 @@
+use super::arima::{IntervalForecast, RESIDUAL_WINDOW, push_residual, residual_window};
+use super::error::{Error, Result};
+use super::optim::{NelderMeadOptions, nelder_mead};
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+
+/// Componente de tendencia de un modelo de suavizado exponencial
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Trend {
+    None,
+    Additive, // Tendencia lineal de Holt
+    Damped,   // Tendencia amortiguada con factor phi < 1
+}
+
+/// Componente estacional, con su periodo
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Seasonality {
+    None,
+    Additive(usize),       // Amplitud estacional constante
+    Multiplicative(usize), // Amplitud proporcional al nivel; requiere datos positivos
+}
+
+impl Seasonality {
+    fn period(&self) -> usize {
+        match self {
+            Seasonality::None => 0,
+            Seasonality::Additive(m) | Seasonality::Multiplicative(m) => *m,
+        }
+    }
+}
+
+/// Estructura de un modelo de suavizado exponencial (error aditivo)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EtsSpec {
+    pub trend: Trend,
+    pub seasonality: Seasonality,
+}
+
+impl EtsSpec {
+    /// Suavizado exponencial simple: solo nivel
+    pub fn simple() -> Self {
+        EtsSpec {
+            trend: Trend::None,
+            seasonality: Seasonality::None,
+        }
+    }
+
+    /// Metodo lineal de Holt, opcionalmente amortiguado
+    pub fn holt(damped: bool) -> Self {
+        EtsSpec {
+            trend: if damped {
+                Trend::Damped
+            } else {
+                Trend::Additive
+            },
+            seasonality: Seasonality::None,
+        }
+    }
+
+    /// Holt-Winters con tendencia aditiva y estacionalidad de periodo `period`
+    pub fn holt_winters(period: usize, multiplicative: bool) -> Self {
+        EtsSpec {
+            trend: Trend::Additive,
+            seasonality: if multiplicative {
+                Seasonality::Multiplicative(period)
+            } else {
+                Seasonality::Additive(period)
+            },
+        }
+    }
+
+    // Notacion de Hyndman et al. (2008), p. ej. "ETS(A,Ad,M)"
+    fn label(&self) -> String {
+        let trend = match self.trend {
+            Trend::None => "N",
+            Trend::Additive => "A",
+            Trend::Damped => "Ad",
+        };
+        let season = match self.seasonality {
+            Seasonality::None => "N",
+            Seasonality::Additive(_) => "A",
+            Seasonality::Multiplicative(_) => "M",
+        };
+        format!("ETS(A,{},{})", trend, season)
+    }
+
+    // Parametros de suavizado libres: alpha, beta, gamma y phi segun el caso
+    fn n_smoothing(&self) -> usize {
+        1 + match self.trend {
+            Trend::None => 0,
+            Trend::Additive => 1,
+            Trend::Damped => 2,
+        } + (self.seasonality != Seasonality::None) as usize
+    }
+}
+
+/// Parametros de suavizado
+///
+/// Los que no aplican a la especificacion se ignoran (beta sin tendencia,
+/// gamma sin estacionalidad, phi salvo con tendencia amortiguada).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EtsParams {
+    pub alpha: f64, // Nivel, en (0, 1)
+    pub beta: f64,  // Tendencia, en (0, 1)
+    pub gamma: f64, // Estacionalidad, en (0, 1 - alpha)
+    pub phi: f64,   // Amortiguamiento, en (0.8, 0.98) al optimizar
+}
+
+impl EtsParams {
+    // Mapea parametros sin restriccion a la region admisible, lejos de los
+    // bordes donde el modelo degenera
+    fn from_unconstrained(x: &[f64], spec: &EtsSpec) -> Self {
+        let mut values = x.iter().copied();
+        let mut next = |low: f64, high: f64| {
+            let v = values.next().unwrap_or(0.0);
+            low + (high - low) / (1.0 + (-v).exp())
+        };
+        let alpha = next(1e-4, 1.0 - 1e-4);
+        let beta = match spec.trend {
+            Trend::None => 0.0,
+            _ => next(1e-4, 1.0 - 1e-4),
+        };
+        let phi = match spec.trend {
+            Trend::Damped => next(0.8, 0.98),
+            _ => 1.0,
+        };
+        let gamma = match spec.seasonality {
+            Seasonality::None => 0.0,
+            _ => (1.0 - alpha) * next(1e-4, 1.0 - 1e-4),
+        };
+        EtsParams {
+            alpha,
+            beta,
+            gamma,
+            phi,
+        }
+    }
+
+    fn check(&self, spec: &EtsSpec) -> Result<()> {
+        let open = |v: f64| v > 0.0 && v < 1.0;
+        if !open(self.alpha) {
+            return Err(Error::Model("Alpha must be in (0, 1)".into()));
+        }
+        if spec.trend != Trend::None && !open(self.beta) {
+            return Err(Error::Model("Beta must be in (0, 1)".into()));
+        }
+        if spec.trend == Trend::Damped && !(self.phi > 0.0 && self.phi <= 1.0) {
+            return Err(Error::Model("Phi must be in (0, 1]".into()));
+        }
+        if spec.seasonality != Seasonality::None
+            && !(self.gamma > 0.0 && self.gamma < 1.0 - self.alpha)
+        {
+            return Err(Error::Model("Gamma must be in (0, 1 - alpha)".into()));
+        }
+        Ok(())
+    }
+}
+
+// Nivel, tendencia y factores estacionales; `season` va del mas antiguo al
+// mas reciente, asi que el frente corresponde al proximo periodo
+#[derive(Debug, Clone)]
+struct EtsState {
+    level: f64,
+    trend: f64,
+    season: VecDeque<f64>,
+}
+
+impl EtsState {
+    // Valores iniciales heuristicos (Hyndman et al., 2008, sec. 2.6.1).
+    // Devuelve el estado anterior a la primera observacion, asi todas las
+    // especificaciones filtran la serie completa y sus verosimilitudes son
+    // comparables.
+    fn initial(series: &[f64], spec: &EtsSpec) -> Self {
+        let m = spec.seasonality.period();
+        if m == 0 {
+            let trend = match spec.trend {
+                Trend::None => 0.0,
+                _ => series[1] - series[0],
+            };
+            return EtsState {
+                level: series[0] - trend,
+                trend,
+                season: VecDeque::new(),
+            };
+        }
+
+        // Medias de los dos primeros ciclos; el nivel se lleva un paso antes del primero
+        let mean = |s: &[f64]| s.iter().sum::<f64>() / s.len() as f64;
+        let (first, second) = (mean(&series[..m]), mean(&series[m..2 * m]));
+        let trend = match spec.trend {
+            Trend::None => 0.0,
+            _ => (second - first) / m as f64,
+        };
+        let season = series[..m]
+            .iter()
+            .enumerate()
+            .map(|(i, y)| {
+                let local = first + trend * (i as f64 - (m as f64 - 1.0) / 2.0);
+                match spec.seasonality {
+                    Seasonality::Multiplicative(_) => y / local,
+                    _ => y - local,
+                }
+            })
+            .collect();
+        EtsState {
+            level: first - trend * (m as f64 + 1.0) / 2.0,
+            trend,
+            season,
+        }
+    }
+
+    // Pronostico a `h` pasos (h >= 1) desde el estado actual
+    fn forecast(&self, spec: &EtsSpec, params: &EtsParams, h: usize) -> f64 {
+        let damping: f64 = match spec.trend {
+            Trend::None => 0.0,
+            Trend::Additive => h as f64,
+            Trend::Damped => (1..=h).map(|j| params.phi.powi(j as i32)).sum(),
+        };
+        let base = self.level + damping * self.trend;
+        match spec.seasonality {
+            Seasonality::None => base,
+            Seasonality::Additive(m) => base + self.season[(h - 1) % m],
+            Seasonality::Multiplicative(m) => base * self.season[(h - 1) % m],
+        }
+    }
+
+    // Ecuaciones de correccion con una observacion; devuelve el error a un paso
+    fn step(&mut self, spec: &EtsSpec, params: &EtsParams, y: f64) -> f64 {
+        let error = y - self.forecast(spec, params, 1);
+        let phi = if spec.trend == Trend::Damped {
+            params.phi
+        } else {
+            1.0
+        };
+        let base = self.level + phi * self.trend;
+        let seasonal = self.season.pop_front();
+
+        let (deseasonalized, season) = match (spec.seasonality, seasonal) {
+            (Seasonality::Additive(_), Some(s)) => (
+                y - s,
+                Some(params.gamma * (y - base) + (1.0 - params.gamma) * s),
+            ),
+            (Seasonality::Multiplicative(_), Some(s)) => (
+                y / s,
+                Some(params.gamma * (y / base) + (1.0 - params.gamma) * s),
+            ),
+            _ => (y, None),
+        };
+        let level = params.alpha * deseasonalized + (1.0 - params.alpha) * base;
+        if spec.trend != Trend::None {
+            self.trend =
+                params.beta * (level - self.level) + (1.0 - params.beta) * phi * self.trend;
+        }
+        self.level = level;
+        if let Some(s) = season {
+            self.season.push_back(s);
+        }
+        error
+    }
+}
+
+/// Modelo de suavizado exponencial ajustado: simple, Holt (amortiguado o no)
+/// y Holt-Winters aditivo o multiplicativo
+#[derive(Debug, Clone)]
+pub struct EtsModel {
+    pub params: EtsParams,
+    spec: EtsSpec,
+    state: EtsState,
+    sigma2: f64,
+    log_likelihood: f64,
+    nobs: usize,    // Errores que entran en la verosimilitud, fijo desde el ajuste
+    updates: usize, // Observaciones incorporadas con `update`
+    residuals: Vec<f64>,
+}
+
+impl EtsModel {
+    /// Ajusta el modelo eligiendo los parametros de suavizado que minimizan
+    /// la suma de cuadrados de los errores a un paso (Nelder-Mead)
+    pub fn fit(series: &[f64], spec: &EtsSpec) -> Result<Self> {
+        check_series(series, spec)?;
+        let initial = EtsState::initial(series, spec);
+
+        let sse = |x: &[f64]| {
+            let params = EtsParams::from_unconstrained(x, spec);
+            let mut state = initial.clone();
+            series
+                .iter()
+                .map(|&y| state.step(spec, &params, y).powi(2))
+                .sum::<f64>()
+        };
+        // alpha = 0.3, beta = 0.1, phi = 0.9, gamma = 0.1 (1 - alpha)
+        let logit = |p: f64| (p / (1.0 - p)).ln();
+        let mut x0 = vec![logit(0.3)];
+        if spec.trend != Trend::None {
+            x0.push(logit(0.1));
+        }
+        if spec.trend == Trend::Damped {
+            x0.push(logit(0.5));
+        }
+        if spec.seasonality != Seasonality::None {
+            x0.push(logit(0.1 / 0.7));
+        }
+        let result = nelder_mead(sse, &x0, &NelderMeadOptions::default());
+        if !result.value.is_finite() {
+            return Err(Error::Optimization(
+                "Smoothing parameter optimization failed".into(),
+            ));
+        }
+        if !result.converged {
+            return Err(Error::Optimization(format!(
+                "Smoothing parameter optimization did not converge in {} iterations",
+                result.iterations
+            )));
+        }
+        Self::filter(
+            series,
+            spec,
+            EtsParams::from_unconstrained(&result.x, spec),
+            initial,
+        )
+    }
+
+    /// Ajusta con parametros de suavizado fijos; los estados iniciales son
+    /// los heuristicos, como en `fit`
+    pub fn fit_with_params(series: &[f64], spec: &EtsSpec, params: EtsParams) -> Result<Self> {
+        check_series(series, spec)?;
+        params.check(spec)?;
+        Self::filter(series, spec, params, EtsState::initial(series, spec))
+    }
+
+    // Recorre la serie desde el estado inicial y guarda residuos y varianza
+    fn filter(
+        series: &[f64],
+        spec: &EtsSpec,
+        params: EtsParams,
+        mut state: EtsState,
+    ) -> Result<Self> {
+        let residuals: Vec<f64> = series
+            .iter()
+            .map(|&y| state.step(spec, &params, y))
+            .collect();
+        let nobs = residuals.len();
+        let sigma2 = residuals.iter().map(|e| e * e).sum::<f64>() / nobs as f64;
+        if !sigma2.is_finite() {
+            return Err(Error::Optimization(
+                "Exponential smoothing filter diverged".into(),
+            ));
+        }
+        let log_likelihood =
+            -0.5 * nobs as f64 * ((2.0 * PI * sigma2.max(f64::MIN_POSITIVE)).ln() + 1.0);
+        Ok(EtsModel {
+            params,
+            spec: *spec,
+            state,
+            sigma2,
+            log_likelihood,
+            nobs,
+            updates: 0,
+            residuals,
+        })
+    }
+
+    pub fn spec(&self) -> EtsSpec {
+        self.spec
+    }
+
+    /// Nombre en la notacion ETS(error, tendencia, estacionalidad)
+    pub fn label(&self) -> String {
+        self.spec.label()
+    }
+
+    /// Nivel, tendencia y factores estacionales actuales (del proximo periodo en adelante)
+    pub fn components(&self) -> (f64, f64, Vec<f64>) {
+        (
+            self.state.level,
+            self.state.trend,
+            self.state.season.iter().copied().collect(),
+        )
+    }
+
+    /// Pronostico de los proximos `steps` valores
+    pub fn forecast(&self, steps: usize) -> Vec<f64> {
+        (1..=steps)
+            .map(|h| self.state.forecast(&self.spec, &self.params, h))
+            .collect()
+    }
+
+    /// Pronostico con errores estandar e intervalos gaussianos
+    ///
+    /// Usa la varianza de Hyndman et al. (2008, tabla 6.1):
+    /// sigma^2 (1 + sum_{j<h} c_j^2), con c_j = alpha + alpha beta phi_j + gamma
+    /// en los multiplos del periodo. Con estacionalidad multiplicativa el
+    /// efecto de cada error se escala por la razon de factores estacionales,
+    /// una aproximacion de primer orden.
+    pub fn forecast_with_intervals(
+        &self,
+        steps: usize,
+        levels: &[f64],
+    ) -> Result<IntervalForecast> {
+        let EtsParams {
+            alpha,
+            beta,
+            gamma,
+            phi,
+        } = self.params;
+        let m = self.spec.seasonality.period();
+        let season = |k: usize| match self.spec.seasonality {
+            Seasonality::Multiplicative(_) => self.state.season[(k - 1) % m],
+            _ => 1.0,
+        };
+
+        let std_error = (1..=steps)
+            .map(|h| {
+                let mut cumulative = 1.0;
+                let mut phi_j = 0.0;
+                for j in 1..h {
+                    phi_j += match self.spec.trend {
+                        Trend::None => 0.0,
+                        Trend::Additive => 1.0,
+                        Trend::Damped => phi.powi(j as i32),
+                    };
+                    let seasonal = if m > 0 && j % m == 0 { gamma } else { 0.0 };
+                    let c = (alpha + alpha * beta * phi_j) * season(h) / season(h - j) + seasonal;
+                    cumulative += c * c;
+                }
+                (self.sigma2 * cumulative).sqrt()
+            })
+            .collect();
+        IntervalForecast::gaussian(self.forecast(steps), std_error, levels)
+    }
+
+    /// Incorpora una observacion sin reajustar los parametros y devuelve el
+    /// pronostico del paso siguiente
+    pub fn update(&mut self, observation: f64) -> Result<f64> {
+        self.check_observation(observation)?;
+        // La verosimilitud y los criterios de informacion quedan los del ajuste
+        let error = self.state.step(&self.spec, &self.params, observation);
+        let capacity = self.nobs.max(RESIDUAL_WINDOW);
+        push_residual(&mut self.residuals, error, capacity);
+        self.updates += 1;
+        Ok(self.state.forecast(&self.spec, &self.params, 1))
+    }
+
+    /// Verifica que `update` aceptaria la observacion: finita, y positiva con
+    /// estacionalidad multiplicativa
+    pub fn check_observation(&self, observation: f64) -> Result<()> {
+        if !observation.is_finite() {
+            return Err(Error::Data("Observation must be a finite number".into()));
+        }
+        if matches!(self.spec.seasonality, Seasonality::Multiplicative(_)) && observation <= 0.0 {
+            return Err(Error::Data(
+                "Multiplicative seasonality requires positive observations".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Varianza de los errores a un paso dentro de la muestra
+    pub fn sigma2(&self) -> f64 {
+        self.sigma2
+    }
+
+    /// Errores a un paso de toda la serie de ajuste, seguidos de los
+    /// de `update`; se conservan los ultimos `max(nobs, RESIDUAL_WINDOW)`
+    pub fn residuals(&self) -> &[f64] {
+        residual_window(&self.residuals, self.nobs.max(RESIDUAL_WINDOW))
+    }
+
+    /// Log-verosimilitud gaussiana concentrada de los errores a un paso
+    pub fn log_likelihood(&self) -> f64 {
+        self.log_likelihood
+    }
+
+    pub fn nobs(&self) -> usize {
+        self.nobs
+    }
+
+    /// Observaciones incorporadas con `update` desde el ajuste
+    pub fn updates(&self) -> usize {
+        self.updates
+    }
+
+    // Suavizado y la varianza: los estados iniciales son heuristicos, no se estiman
+    pub(crate) fn n_params(&self) -> usize {
+        self.spec.n_smoothing() + 1
+    }
+}
+
+fn check_series(series: &[f64], spec: &EtsSpec) -> Result<()> {
+    if series.iter().any(|v| !v.is_finite()) {
+        return Err(Error::Data(
+            "Series contains missing values; fill gaps before fitting".into(),
+        ));
+    }
+    let m = spec.seasonality.period();
+    if spec.seasonality != Seasonality::None && m < 2 {
+        return Err(Error::Model("Seasonal period must be at least 2".into()));
+    }
+    let minimum = if m > 0 { 2 * m + 2 } else { 4 } + spec.n_smoothing();
+    if series.len() < minimum {
+        return Err(Error::Data(format!(
+            "{} needs at least {} observations, got {}",
+            spec.label(),
+            minimum,
+            series.len()
+        )));
+    }
+    if matches!(spec.seasonality, Seasonality::Multiplicative(_))
+        && series.iter().any(|v| *v <= 0.0)
+    {
+        return Err(Error::Data(
+            "Multiplicative seasonality requires a strictly positive series".into(),
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_ets_models_track_trend_and_season() {
+    use super::forecaster::Forecaster;
+    use super::test_util::Lcg;
+
+    // SES con alpha fijo: l_t = 0.5 y_t + 0.5 l_{t-1} desde l_{-1} = y_0
+    let series = [10.0, 12.0, 11.0, 13.0, 12.0, 14.0];
+    let params = EtsParams {
+        alpha: 0.5,
+        beta: 0.0,
+        gamma: 0.0,
+        phi: 1.0,
+    };
+    let ses = EtsModel::fit_with_params(&series, &EtsSpec::simple(), params).unwrap();
+    let level = series.iter().fold(10.0, |l, y| 0.5 * y + 0.5 * l);
+    assert!((ses.forecast(3)[2] - level).abs() < 1e-12);
+    assert_eq!(ses.residuals()[..2], [0.0, 2.0]);
+    assert_eq!((ses.nobs(), ses.n_params()), (series.len(), 2));
+
+    // Tendencia con estacionalidad multiplicativa de periodo 12 y algo de ruido
+    let mut rng = Lcg::new(5);
+    let data: Vec<f64> = (0..144)
+        .map(|t| {
+            let noise = rng.noise();
+            (50.0 + 0.5 * t as f64) * (1.0 + 0.2 * (2.0 * PI * t as f64 / 12.0).sin()) + noise
+        })
+        .collect();
+    let (train, test) = data.split_at(120);
+    let mae = |model: &EtsModel| {
+        let forecast = model.forecast(test.len());
+        forecast
+            .iter()
+            .zip(test)
+            .map(|(f, y)| (f - y).abs())
+            .sum::<f64>()
+            / test.len() as f64
+    };
+
+    let multiplicative = EtsModel::fit(train, &EtsSpec::holt_winters(12, true)).unwrap();
+    let additive = EtsModel::fit(train, &EtsSpec::holt_winters(12, false)).unwrap();
+    let damped = EtsModel::fit(train, &EtsSpec::holt(true)).unwrap();
+    assert_eq!(multiplicative.label(), "ETS(A,A,M)");
+    assert!(mae(&multiplicative) < 1.5);
+    assert!(mae(&multiplicative) < mae(&additive));
+    assert!(mae(&additive) < mae(&damped));
+    // Todas las especificaciones filtran la misma muestra: los AIC son comparables
+    assert_eq!(
+        (multiplicative.nobs(), damped.nobs()),
+        (train.len(), train.len())
+    );
+    assert!(multiplicative.aic() < damped.aic());
+    let p = damped.params;
+    assert!(p.phi >= 0.8 && p.phi <= 0.98 && p.alpha > 0.0 && p.alpha < 1.0);
+
+    // Actualizar con lo observado equivale a reajustar con los mismos parametros
+    let mut online = multiplicative.clone();
+    for &y in test {
+        online.update(y).unwrap();
+    }
+    let batch = EtsModel::fit_with_params(
+        &data,
+        &EtsSpec::holt_winters(12, true),
+        multiplicative.params,
+    )
+    .unwrap();
+    assert!((online.forecast(1)[0] - batch.forecast(1)[0]).abs() < 1e-9);
+    assert!(online.update(-1.0).is_err());
+}
@@ -0,0 +1,610 @@
+//! Pronostico ARIMA y comparacion ponderada en punto fijo
+//!
+//! Para nodos sin FPU: todas las operaciones son enteras con saturacion, los
+//! productos se acumulan en enteros anchos y se redondean una sola vez. El
+//! nucleo no usa `std`; la conversion desde `ArimaModel` y el reporte de
+//! error contra f64 solo existen con la feature `std`.
+
+use super::embedded::Ring;
+use core::fmt;
+
+/// Formato Qm.n con signo guardado en un `i32`: `int_bits` + `frac_bits` <= 31
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QFormat {
+    pub int_bits: u32,
+    pub frac_bits: u32,
+}
+
+impl QFormat {
+    pub const Q16_16: QFormat = QFormat {
+        int_bits: 15,
+        frac_bits: 16,
+    };
+    pub const Q15: QFormat = QFormat {
+        int_bits: 0,
+        frac_bits: 15,
+    }; // Rango [-1, 1), 16 bits
+    pub const Q3_28: QFormat = QFormat {
+        int_bits: 3,
+        frac_bits: 28,
+    }; // Coeficientes: cubre los de integracion de d = 2
+
+    pub const fn new(int_bits: u32, frac_bits: u32) -> Option<QFormat> {
+        if int_bits + frac_bits > 31 {
+            None
+        } else {
+            Some(QFormat {
+                int_bits,
+                frac_bits,
+            })
+        }
+    }
+
+    pub const fn max_raw(&self) -> i32 {
+        ((1i64 << (self.int_bits + self.frac_bits)) - 1) as i32
+    }
+
+    pub const fn min_raw(&self) -> i32 {
+        (-(1i64 << (self.int_bits + self.frac_bits))) as i32
+    }
+
+    /// Resolucion (valor del bit menos significativo)
+    pub fn epsilon(&self) -> f64 {
+        1.0 / (1u64 << self.frac_bits) as f64
+    }
+
+    /// Recorta un resultado ancho al rango del formato
+    pub const fn saturate(&self, wide: i64) -> i32 {
+        if wide > self.max_raw() as i64 {
+            self.max_raw()
+        } else if wide < self.min_raw() as i64 {
+            self.min_raw()
+        } else {
+            wide as i32
+        }
+    }
+
+    /// Conversion con redondeo al mas cercano y saturacion (NaN da cero)
+    pub fn from_f64(&self, value: f64) -> i32 {
+        let scaled = value * (1u64 << self.frac_bits) as f64;
+        let rounded = if scaled >= 0.0 {
+            scaled + 0.5
+        } else {
+            scaled - 0.5
+        };
+        self.saturate(rounded as i64)
+    }
+
+    pub fn to_f64(&self, raw: i32) -> f64 {
+        raw as f64 * self.epsilon()
+    }
+
+    pub const fn add(&self, a: i32, b: i32) -> i32 {
+        self.saturate(a as i64 + b as i64)
+    }
+
+    pub const fn sub(&self, a: i32, b: i32) -> i32 {
+        self.saturate(a as i64 - b as i64)
+    }
+
+    pub const fn mul(&self, a: i32, b: i32) -> i32 {
+        self.saturate(round_shift(a as i64 * b as i64, self.frac_bits))
+    }
+}
+
+// Notacion Qm.n; los formatos sin bits enteros se escriben Qn (p. ej. Q15)
+impl fmt::Display for QFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.int_bits == 0 {
+            write!(f, "Q{}", self.frac_bits)
+        } else {
+            write!(f, "Q{}.{}", self.int_bits + 1, self.frac_bits)
+        }
+    }
+}
+
+// Desplazamiento aritmetico a la derecha redondeando al mas cercano
+const fn round_shift(value: i64, shift: u32) -> i64 {
+    if shift == 0 {
+        value
+    } else {
+        value.saturating_add(1 << (shift - 1)) >> shift
+    }
+}
+
+// Division entera redondeando al mas cercano; `den` debe ser positivo
+fn round_div(num: i128, den: i128) -> i128 {
+    if num >= 0 {
+        (num + den / 2) / den
+    } else {
+        (num - den / 2) / den
+    }
+}
+
+// Raiz cuadrada entera (parte entera) por el metodo bit a bit
+fn isqrt(value: u128) -> u128 {
+    let mut remainder = value;
+    let mut root = 0u128;
+    let mut bit = 1u128 << 126;
+    while bit > value {
+        bit >>= 2;
+    }
+    while bit != 0 {
+        if remainder >= root + bit {
+            remainder -= root + bit;
+            root = (root >> 1) + bit;
+        } else {
+            root >>= 1;
+        }
+        bit >>= 2;
+    }
+    root
+}
+
+/// Predictor ARIMA en punto fijo con el mismo estado acotado que
+/// `embedded::ArimaPredictor`
+///
+/// La senal (niveles, valores diferenciados, residuos y media) usa `signal`
+/// y los coeficientes usan `coeffs`, que necesita bits enteros para los
+/// coeficientes de integracion (por ejemplo 2 y -1 cuando d = 2).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FixedPredictor<const P: usize, const Q: usize, const L: usize> {
+    signal: QFormat,
+    coeffs: QFormat,
+    ar: [i32; P],
+    ma: [i32; Q],
+    delta: [i32; L],
+    mean: i32,
+    levels: Ring<i32, L>,
+    values: Ring<i32, P>,
+    residuals: Ring<i32, Q>,
+    saturations: u32,
+}
+
+impl<const P: usize, const Q: usize, const L: usize> FixedPredictor<P, Q, L> {
+    /// Predictor sin historia; coeficientes en `coeffs` y media en `signal`
+    pub const fn new(
+        signal: QFormat,
+        coeffs: QFormat,
+        ar: [i32; P],
+        ma: [i32; Q],
+        delta: [i32; L],
+        mean: i32,
+    ) -> Self {
+        FixedPredictor {
+            signal,
+            coeffs,
+            ar,
+            ma,
+            delta,
+            mean,
+            levels: Ring::new([0; L]),
+            values: Ring::new([mean; P]),
+            residuals: Ring::new([0; Q]),
+            saturations: 0,
+        }
+    }
+
+    /// Fija el origen del pronostico; cada arreglo va del mas antiguo al mas reciente
+    pub const fn with_state(
+        mut self,
+        levels: [i32; L],
+        values: [i32; P],
+        residuals: [i32; Q],
+    ) -> Self {
+        self.levels = Ring::new(levels);
+        self.values = Ring::new(values);
+        self.residuals = Ring::new(residuals);
+        self
+    }
+
+    /// Numero de operaciones que tuvieron que saturar desde la creacion
+    pub fn saturations(&self) -> u32 {
+        self.saturations
+    }
+
+    // Pasa un acumulador con frac_bits de senal + coeficientes al formato de
+    // la senal, contando las saturaciones
+    fn narrow(&mut self, acc: i64) -> i32 {
+        let wide = round_shift(acc, self.coeffs.frac_bits);
+        let value = self.signal.saturate(wide);
+        if value as i64 != wide {
+            self.saturations += 1;
+        }
+        value
+    }
+
+    // Resta en el formato de la senal, contando la saturacion
+    fn sub(&mut self, a: i32, b: i32) -> i32 {
+        let wide = a as i64 - b as i64;
+        let value = self.signal.saturate(wide);
+        if value as i64 != wide {
+            self.saturations += 1;
+        }
+        value
+    }
+
+    fn predict_value(&mut self) -> i32 {
+        let mut acc = (self.mean as i64) << self.coeffs.frac_bits;
+        for (j, &coeff) in self.ar.iter().enumerate() {
+            let centered = self.values.lag(j) as i64 - self.mean as i64;
+            acc = acc.saturating_add(coeff as i64 * centered);
+        }
+        for (j, &coeff) in self.ma.iter().enumerate() {
+            acc = acc.saturating_add(coeff as i64 * self.residuals.lag(j) as i64);
+        }
+        self.narrow(acc)
+    }
+
+    fn integrate(&mut self, value: i32) -> i32 {
+        let mut acc = (value as i64) << self.coeffs.frac_bits;
+        for (k, &coeff) in self.delta.iter().enumerate() {
+            acc = acc.saturating_add(coeff as i64 * self.levels.lag(k) as i64);
+        }
+        self.narrow(acc)
+    }
+
+    /// Pronostico del siguiente paso
+    pub fn predict(&mut self) -> i32 {
+        let value = self.predict_value();
+        self.integrate(value)
+    }
+
+    /// Agrega una observacion y devuelve el pronostico del siguiente paso
+    pub fn update(&mut self, observation: i32) -> i32 {
+        let base = self.integrate(0);
+        let value = self.sub(observation, base);
+        let predicted = self.predict_value();
+        let residual = self.sub(value, predicted);
+        self.levels.push(observation);
+        self.values.push(value);
+        self.residuals.push(residual);
+        self.predict()
+    }
+
+    /// Pronostico de varios pasos, uno por elemento de `out`
+    pub fn forecast(&mut self, out: &mut [i32]) {
+        let mut state = *self;
+        for slot in out.iter_mut() {
+            let value = state.predict_value();
+            let level = state.integrate(value);
+            state.levels.push(level);
+            state.values.push(value);
+            state.residuals.push(0);
+            *slot = level;
+        }
+        self.saturations = state.saturations;
+    }
+}
+
+/// Metricas de `weighted_comparison`, en el formato de la senal
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FixedComparison {
+    pub weighted_error: i32,     // ME Ponderado
+    pub weighted_abs_error: i32, // MAE Ponderado
+    pub weighted_rmse: i32,      // RMSE Ponderado
+    pub saturated: bool,         // Algun resultado quedo fuera de rango
+}
+
+/// Version en punto fijo de `calculate_weighted_comparison`
+///
+/// Parameters:
+/// - format: formato Q de `actual` y `predicted`
+/// - weights: pesos no negativos en cualquier escala entera (se normalizan
+///   dividiendo por su suma); `None` da pesos iguales
+/// - weighted_values: si se entrega, recibe w_i * (actual_i + predicted_i) / 2
+///
+/// Returns:
+/// - `None` si los largos no coinciden, no hay datos o los pesos suman cero
+pub fn weighted_comparison(
+    format: QFormat,
+    actual: &[i32],
+    predicted: &[i32],
+    weights: Option<&[i32]>,
+    weighted_values: Option<&mut [i32]>,
+) -> Option<FixedComparison> {
+    let n = actual.len();
+    if n == 0 || predicted.len() != n || weights.is_some_and(|w| w.len() != n) {
+        return None;
+    }
+    let weight = |i: usize| weights.map_or(1, |w| w[i]) as i128;
+    let weight_sum: i128 = (0..n).map(weight).sum();
+    if weight_sum <= 0 || (0..n).any(|i| weight(i) < 0) {
+        return None;
+    }
+
+    let mut saturated = false;
+    let mut narrow = |wide: i128| {
+        let clamped = wide.clamp(format.min_raw() as i128, format.max_raw() as i128);
+        saturated |= clamped != wide;
+        clamped as i32
+    };
+
+    if let Some(out) = weighted_values {
+        if out.len() != n {
+            return None;
+        }
+        for (i, slot) in out.iter_mut().enumerate() {
+            let sum = actual[i] as i128 + predicted[i] as i128;
+            *slot = narrow(round_div(weight(i) * sum, 2 * weight_sum));
+        }
+    }
+
+    // Acumuladores exactos: el error al cuadrado lleva el doble de bits fraccionarios
+    let mut error = 0i128;
+    let mut abs_error = 0i128;
+    let mut squared_error = 0i128;
+    for i in 0..n {
+        let e = actual[i] as i128 - predicted[i] as i128;
+        error += weight(i) * e;
+        abs_error += weight(i) * e.abs();
+        squared_error += weight(i) * e * e;
+    }
+
+    let mean_squared = round_div(squared_error, weight_sum) as u128;
+    Some(FixedComparison {
+        weighted_error: narrow(round_div(error, weight_sum)),
+        weighted_abs_error: narrow(round_div(abs_error, weight_sum)),
+        weighted_rmse: narrow(isqrt(mean_squared) as i128),
+        saturated,
+    })
+}
+
+#[cfg(feature = "std")]
+pub use host::*;
+
+// Conversion desde el modelo en f64 y reporte de error en el host
+#[cfg(feature = "std")]
+mod host {
+    use super::super::arima::ArimaModel;
+    use super::super::arima_eval::{
+        WeightingStrategy, calculate_weighted_comparison, generate_weights,
+    };
+    use super::super::data_struct::{extract_series, read_csv};
+    use super::super::error::{Error, Result};
+    use super::{FixedPredictor, QFormat, fmt, weighted_comparison};
+
+    /// Formatos y escala de la senal: se guarda (y - offset) / scale
+    ///
+    /// Con Q15 la senal debe caber en [-1, 1), asi que `offset` y `scale`
+    /// deben centrar y acotar el rango del sensor.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct FixedConfig {
+        pub signal: QFormat,
+        pub coeffs: QFormat,
+        pub offset: f64,
+        pub scale: f64,
+    }
+
+    impl FixedConfig {
+        pub fn q16_16() -> Self {
+            FixedConfig {
+                signal: QFormat::Q16_16,
+                coeffs: QFormat::Q3_28,
+                offset: 0.0,
+                scale: 1.0,
+            }
+        }
+
+        pub fn q15(offset: f64, scale: f64) -> Self {
+            FixedConfig {
+                signal: QFormat::Q15,
+                coeffs: QFormat::Q3_28,
+                offset,
+                scale,
+            }
+        }
+
+        pub fn encode(&self, value: f64) -> i32 {
+            self.signal.from_f64((value - self.offset) / self.scale)
+        }
+
+        pub fn decode(&self, raw: i32) -> f64 {
+            self.signal.to_f64(raw) * self.scale + self.offset
+        }
+
+        // Diferencias de la senal: el offset se cancela
+        fn encode_difference(&self, value: f64) -> i32 {
+            self.signal.from_f64(value / self.scale)
+        }
+    }
+
+    fn to_array<const N: usize>(values: Vec<i32>, what: &str) -> Result<[i32; N]> {
+        let len = values.len();
+        values.try_into().map_err(|_| {
+            Error::Model(format!(
+                "Model has {} {} terms, predictor expects {}",
+                len, what, N
+            ))
+        })
+    }
+
+    impl<const P: usize, const Q: usize, const L: usize> FixedPredictor<P, Q, L> {
+        /// Cuantiza un modelo ajustado (coeficientes expandidos y estado actual)
+        pub fn from_model(model: &ArimaModel, config: &FixedConfig) -> Result<Self> {
+            if !model.exog_coeffs.is_empty() {
+                return Err(Error::Model(
+                    "Fixed-point predictor does not support exogenous regressors".into(),
+                ));
+            }
+            if config.scale.is_nan() || config.scale <= 0.0 {
+                return Err(Error::Model("Signal scale must be positive".into()));
+            }
+            let parts = model.predictor_parts();
+            let quantize = |values: &[f64]| -> Vec<i32> {
+                values.iter().map(|c| config.coeffs.from_f64(*c)).collect()
+            };
+
+            // Con diferenciacion sum(delta) = 1 y el offset no llega a los
+            // valores diferenciados; sin ella se resta completo
+            let carried = 1.0 - parts.delta.iter().sum::<f64>();
+            let shift = |v: f64| config.encode_difference(v - config.offset * carried);
+
+            let predictor = FixedPredictor::new(
+                config.signal,
+                config.coeffs,
+                to_array(quantize(&parts.ar), "AR")?,
+                to_array(quantize(&parts.ma), "MA")?,
+                to_array(quantize(&parts.delta), "integration")?,
+                shift(parts.mean),
+            );
+            Ok(predictor.with_state(
+                to_array(
+                    parts.levels.iter().map(|v| config.encode(*v)).collect(),
+                    "level",
+                )?,
+                to_array(parts.values.iter().map(|v| shift(*v)).collect(), "AR state")?,
+                to_array(
+                    parts
+                        .residuals
+                        .iter()
+                        .map(|v| config.encode_difference(*v))
+                        .collect(),
+                    "MA state",
+                )?,
+            ))
+        }
+    }
+
+    /// Error del camino en punto fijo respecto a la referencia f64
+    #[derive(Debug, Clone)]
+    pub struct FixedErrorReport {
+        pub config: FixedConfig,
+        pub steps: usize,
+        pub max_abs_error: f64, // Pronosticos a un paso, en unidades del sensor
+        pub mean_abs_error: f64,
+        pub rmse: f64,
+        pub saturations: u32,
+        pub comparison_error: f64, // Mayor diferencia en ME, MAE y RMSE ponderados
+    }
+
+    impl fmt::Display for FixedErrorReport {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "{:<8} max |e| {:.3e}  MAE {:.3e}  RMSE {:.3e}  saturaciones {}  err. ponderado {:.3e}",
+                self.config.signal.to_string(),
+                self.max_abs_error,
+                self.mean_abs_error,
+                self.rmse,
+                self.saturations,
+                self.comparison_error
+            )
+        }
+    }
+
+    /// Ejecuta en paralelo el modelo f64 y el predictor en punto fijo sobre
+    /// `observations` (pronosticos a un paso) y mide la diferencia
+    pub fn compare_with_reference<const P: usize, const Q: usize, const L: usize>(
+        model: &ArimaModel,
+        observations: &[f64],
+        config: &FixedConfig,
+    ) -> Result<FixedErrorReport> {
+        if observations.is_empty() {
+            return Err(Error::Evaluation("No observations to compare".into()));
+        }
+        let mut reference = model.clone();
+        let mut fixed = FixedPredictor::<P, Q, L>::from_model(model, config)?;
+
+        let mut expected = vec![reference.forecast(1)[0]];
+        let mut raw = vec![fixed.predict()];
+        for &y in &observations[..observations.len() - 1] {
+            expected.push(reference.update(y)?);
+            raw.push(fixed.update(config.encode(y)));
+        }
+
+        let errors: Vec<f64> = raw
+            .iter()
+            .zip(&expected)
+            .map(|(r, e)| config.decode(*r) - e)
+            .collect();
+        let n = errors.len() as f64;
+
+        // Misma comparacion ponderada en ambos caminos
+        let weights = generate_weights(observations.len(), WeightingStrategy::Linear)?;
+        let float = calculate_weighted_comparison(observations, &expected, Some(&weights))?;
+        let fixed_weights: Vec<i32> = weights
+            .iter()
+            .map(|w| QFormat::Q3_28.from_f64(*w))
+            .collect();
+        let encoded: Vec<i32> = observations.iter().map(|y| config.encode(*y)).collect();
+        let quantized =
+            weighted_comparison(config.signal, &encoded, &raw, Some(&fixed_weights), None)
+                .ok_or_else(|| {
+                    Error::Evaluation("Fixed-point weighted comparison failed".into())
+                })?;
+        let to_units = |raw: i32| config.signal.to_f64(raw) * config.scale;
+        let comparison_error = [
+            (quantized.weighted_error, float.weighted_error),
+            (quantized.weighted_abs_error, float.weighted_abs_error),
+            (quantized.weighted_rmse, float.weighted_rmse),
+        ]
+        .iter()
+        .map(|(q, f)| (to_units(*q) - f).abs())
+        .fold(0.0, f64::max);
+
+        Ok(FixedErrorReport {
+            config: *config,
+            steps: errors.len(),
+            max_abs_error: errors.iter().fold(0.0, |m, e| m.max(e.abs())),
+            mean_abs_error: errors.iter().map(|e| e.abs()).sum::<f64>() / n,
+            rmse: (errors.iter().map(|e| e * e).sum::<f64>() / n).sqrt(),
+            saturations: fixed.saturations(),
+            comparison_error,
+        })
+    }
+
+    /// Reporte de error Q16.16 y Q15 contra f64 para la temperatura de un CSV
+    ///
+    /// Ajusta un ARIMA(2,1,1) con el 80% de los datos y compara los
+    /// pronosticos a un paso sobre el 20% restante.
+    pub fn fixed_point_report(filepath: &str) -> Result<Vec<FixedErrorReport>> {
+        let data = read_csv(filepath)?;
+        let series = extract_series(&data, "Temperature")?;
+        let train_size = (series.len() as f64 * 0.8) as usize;
+        let model = ArimaModel::fit_series(&series[..train_size], 2, 1, 1)?;
+        let test = &series[train_size..];
+
+        // Q15 centrado en el rango de entrenamiento con margen del doble
+        let (low, high) = series[..train_size]
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(l, h), v| {
+                (l.min(*v), h.max(*v))
+            });
+        let q15 = FixedConfig::q15((low + high) / 2.0, (high - low).max(1e-6));
+
+        [FixedConfig::q16_16(), q15]
+            .iter()
+            .map(|config| compare_with_reference::<2, 1, 1>(&model, test, config))
+            .collect()
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_fixed_point_tracks_f64_on_sensor_data() {
+    let q = QFormat::Q16_16;
+    assert_eq!(q.mul(q.from_f64(1.5), q.from_f64(-2.0)), q.from_f64(-3.0));
+    assert_eq!(QFormat::Q15.add(QFormat::Q15.from_f64(0.75), 30000), 32767);
+    assert_eq!(
+        weighted_comparison(q, &[q.from_f64(3.0)], &[q.from_f64(1.0)], None, None)
+            .map(|c| c.weighted_rmse),
+        Some(q.from_f64(2.0))
+    );
+
+    // La resta que arma el valor diferenciado tambien cuenta como saturacion
+    let one = QFormat::Q3_28.from_f64(1.0);
+    let mut edge = FixedPredictor::<0, 0, 1>::new(QFormat::Q15, QFormat::Q3_28, [], [], [one], 0)
+        .with_state([QFormat::Q15.min_raw()], [], []);
+    edge.update(QFormat::Q15.max_raw());
+    assert_eq!(edge.saturations(), 1);
+
+    let reports = fixed_point_report("sensor_data.csv").unwrap();
+    assert!(reports[0].to_string().starts_with("Q16.16"));
+    assert!(reports[1].to_string().starts_with("Q15 "));
+    let (q16, q15) = (&reports[0], &reports[1]);
+    assert_eq!(q16.saturations, 0);
+    assert!(q16.max_abs_error < 1e-3, "{:?}", q16);
+    assert!(q15.max_abs_error < 1e-2, "{:?}", q15);
+    assert!(q16.comparison_error < 1e-3 && q15.comparison_error < 1e-2);
+}
@@ -0,0 +1,340 @@
+use super::arima::{ArimaModel, ArimaSpec, IntervalForecast};
+use super::data_struct::{Dataset, extract_series};
+use super::error::{Error, Result};
+use super::ets::{EtsModel, EtsSpec};
+use super::metrics::{EvaluationOptions, MetricsReport, evaluate};
+
+/// Interfaz comun de los modelos de pronostico ya ajustados
+///
+/// Permite que el backtest, las metricas y los ensambles traten igual a
+/// ARIMA y a los modelos de suavizado exponencial. Es segura para objetos,
+/// asi que se puede usar como `Box<dyn Forecaster>`.
+pub trait Forecaster {
+    /// Nombre corto para reportes, p. ej. "ARIMA(2,1,1)" o "ETS(A,Ad,N)"
+    fn name(&self) -> String;
+
+    /// Pronostico puntual de los proximos `steps` valores
+    fn forecast(&self, steps: usize) -> Vec<f64>;
+
+    /// Pronostico con errores estandar e intervalos a los niveles dados (p. ej. &[0.8, 0.95])
+    fn forecast_with_intervals(&self, steps: usize, levels: &[f64]) -> Result<IntervalForecast>;
+
+    /// Incorpora una observacion sin reajustar y devuelve el pronostico a un paso
+    fn update(&mut self, observation: f64) -> Result<f64>;
+
+    /// Indica si `update` aceptaria la observacion, sin modificar el modelo
+    fn check_observation(&self, observation: f64) -> Result<()> {
+        if !observation.is_finite() {
+            return Err(Error::Data("Observation must be a finite number".into()));
+        }
+        Ok(())
+    }
+
+    /// Errores a un paso dentro de la muestra
+    fn residuals(&self) -> &[f64];
+
+    /// Log-verosimilitud gaussiana del ajuste
+    fn log_likelihood(&self) -> f64;
+
+    /// Observaciones que entran en la verosimilitud
+    fn nobs(&self) -> usize;
+
+    /// Parametros estimados, varianza incluida
+    fn n_params(&self) -> usize;
+
+    fn aic(&self) -> f64 {
+        -2.0 * self.log_likelihood() + 2.0 * self.n_params() as f64
+    }
+
+    /// AIC con correccion de muestra pequena
+    fn aicc(&self) -> f64 {
+        let k = self.n_params() as f64;
+        let n = self.nobs() as f64;
+        if n - k - 1.0 <= 0.0 {
+            return f64::INFINITY;
+        }
+        self.aic() + 2.0 * k * (k + 1.0) / (n - k - 1.0)
+    }
+
+    fn bic(&self) -> f64 {
+        -2.0 * self.log_likelihood() + self.n_params() as f64 * (self.nobs() as f64).ln()
+    }
+}
+
+// Permite usar modelos en caja (p. ej. miembros de un ensamble) donde se pide un `Forecaster`
+impl<F: Forecaster + ?Sized> Forecaster for Box<F> {
+    fn name(&self) -> String {
+        (**self).name()
+    }
+
+    fn forecast(&self, steps: usize) -> Vec<f64> {
+        (**self).forecast(steps)
+    }
+
+    fn forecast_with_intervals(&self, steps: usize, levels: &[f64]) -> Result<IntervalForecast> {
+        (**self).forecast_with_intervals(steps, levels)
+    }
+
+    fn update(&mut self, observation: f64) -> Result<f64> {
+        (**self).update(observation)
+    }
+
+    fn check_observation(&self, observation: f64) -> Result<()> {
+        (**self).check_observation(observation)
+    }
+
+    fn residuals(&self) -> &[f64] {
+        (**self).residuals()
+    }
+
+    fn log_likelihood(&self) -> f64 {
+        (**self).log_likelihood()
+    }
+
+    fn nobs(&self) -> usize {
+        (**self).nobs()
+    }
+
+    fn n_params(&self) -> usize {
+        (**self).n_params()
+    }
+
+    fn aic(&self) -> f64 {
+        (**self).aic()
+    }
+
+    fn aicc(&self) -> f64 {
+        (**self).aicc()
+    }
+
+    fn bic(&self) -> f64 {
+        (**self).bic()
+    }
+}
+
+/// Especificacion de un modelo que sabe ajustarse a una serie
+///
+/// `ArimaSpec` y `EtsSpec` la implementan, asi que un mismo flujo de
+/// evaluacion sirve para cualquier familia de modelos.
+pub trait ForecasterSpec {
+    type Model: Forecaster;
+
+    /// Ajusta el modelo a una serie sin valores faltantes
+    fn fit(&self, series: &[f64]) -> Result<Self::Model>;
+
+    /// Ajusta el modelo a una columna de un `Dataset`
+    fn fit_dataset(&self, data: &Dataset, field: &str) -> Result<Self::Model> {
+        self.fit(&extract_series(data, field)?)
+    }
+}
+
+impl ForecasterSpec for ArimaSpec {
+    type Model = ArimaModel;
+
+    fn fit(&self, series: &[f64]) -> Result<ArimaModel> {
+        ArimaModel::fit_spec(series, self)
+    }
+}
+
+impl ForecasterSpec for EtsSpec {
+    type Model = EtsModel;
+
+    fn fit(&self, series: &[f64]) -> Result<EtsModel> {
+        EtsModel::fit(series, self)
+    }
+}
+
+impl Forecaster for ArimaModel {
+    fn name(&self) -> String {
+        let spec = self.spec();
+        let mut name = format!("ARIMA({},{},{})", spec.p, spec.d, spec.q);
+        if let Some(s) = spec.seasonal {
+            name += &format!("({},{},{})[{}]", s.p, s.d, s.q, s.period);
+        }
+        name
+    }
+
+    fn forecast(&self, steps: usize) -> Vec<f64> {
+        ArimaModel::forecast(self, steps)
+    }
+
+    fn forecast_with_intervals(&self, steps: usize, levels: &[f64]) -> Result<IntervalForecast> {
+        ArimaModel::forecast_with_intervals(self, steps, levels)
+    }
+
+    fn update(&mut self, observation: f64) -> Result<f64> {
+        ArimaModel::update(self, observation)
+    }
+
+    fn residuals(&self) -> &[f64] {
+        ArimaModel::residuals(self)
+    }
+
+    fn log_likelihood(&self) -> f64 {
+        ArimaModel::log_likelihood(self)
+    }
+
+    fn nobs(&self) -> usize {
+        ArimaModel::nobs(self)
+    }
+
+    fn n_params(&self) -> usize {
+        ArimaModel::n_params(self)
+    }
+}
+
+impl Forecaster for EtsModel {
+    fn name(&self) -> String {
+        self.label()
+    }
+
+    fn forecast(&self, steps: usize) -> Vec<f64> {
+        EtsModel::forecast(self, steps)
+    }
+
+    fn forecast_with_intervals(&self, steps: usize, levels: &[f64]) -> Result<IntervalForecast> {
+        EtsModel::forecast_with_intervals(self, steps, levels)
+    }
+
+    fn update(&mut self, observation: f64) -> Result<f64> {
+        EtsModel::update(self, observation)
+    }
+
+    fn check_observation(&self, observation: f64) -> Result<()> {
+        EtsModel::check_observation(self, observation)
+    }
+
+    fn residuals(&self) -> &[f64] {
+        EtsModel::residuals(self)
+    }
+
+    fn log_likelihood(&self) -> f64 {
+        EtsModel::log_likelihood(self)
+    }
+
+    fn nobs(&self) -> usize {
+        EtsModel::nobs(self)
+    }
+
+    fn n_params(&self) -> usize {
+        EtsModel::n_params(self)
+    }
+}
+
+/// Resultado de evaluar un modelo con una particion entrenamiento / prueba
+#[derive(Debug, Clone)]
+pub struct HoldoutEvaluation<M> {
+    pub model: M, // Ajustado solo con el entrenamiento
+    pub training: Vec<f64>,
+    pub actual: Vec<f64>,
+    pub predicted: Vec<f64>,
+    pub report: MetricsReport, // MASE y RMSSE escalados con el entrenamiento
+}
+
+/// Ajusta `spec` con la primera fraccion de la serie y evalua el pronostico
+/// sobre el resto
+///
+/// Parameters:
+/// - spec: especificacion del modelo (ARIMA, ETS, ...)
+/// - series: la serie completa
+/// - train_fraction: fraccion usada para entrenar, en (0, 1)
+/// - options: estrategia de pesos, estacionalidad y cuantiles; `training` se
+///   completa con la parte de entrenamiento si viene vacio
+pub fn evaluate_holdout<S: ForecasterSpec>(
+    spec: &S,
+    series: &[f64],
+    train_fraction: f64,
+    options: &EvaluationOptions,
+) -> Result<HoldoutEvaluation<S::Model>> {
+    if !(train_fraction > 0.0 && train_fraction < 1.0) {
+        return Err(Error::Evaluation(
+            "Training fraction must be in (0, 1)".into(),
+        ));
+    }
+    let train_size = (series.len() as f64 * train_fraction) as usize;
+    if train_size == 0 || train_size == series.len() {
+        return Err(Error::Evaluation(
+            "Both the training and the test part must be non-empty".into(),
+        ));
+    }
+    let (training, actual) = series.split_at(train_size);
+
+    let model = spec.fit(training)?;
+    let predicted = model.forecast(actual.len());
+    let report = evaluate(
+        actual,
+        &predicted,
+        &EvaluationOptions {
+            weighting: options.weighting.clone(),
+            training: options.training.or(Some(training)),
+            season: options.season,
+            interval: options.interval,
+            quantiles: options.quantiles,
+        },
+    )?;
+    Ok(HoldoutEvaluation {
+        model,
+        training: training.to_vec(),
+        actual: actual.to_vec(),
+        predicted,
+        report,
+    })
+}
+
+#[test]
+fn test_forecaster_trait_is_shared_by_arima_and_ets() {
+    use super::test_util::Lcg;
+    let mut rng = Lcg::new(11);
+    let mut level = 20.0;
+    let series: Vec<f64> = (0..300)
+        .map(|_| {
+            level += 0.05 + rng.noise();
+            level
+        })
+        .collect();
+
+    let arima = evaluate_holdout(
+        &ArimaSpec::new(0, 1, 1),
+        &series,
+        0.9,
+        &EvaluationOptions::default(),
+    )
+    .unwrap();
+    let ets = evaluate_holdout(
+        &EtsSpec::holt(true),
+        &series,
+        0.9,
+        &EvaluationOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(arima.actual.len(), 30);
+    assert!(arima.report.mase.is_some());
+
+    // Modelos heterogeneos detras de la misma interfaz
+    let mut models: Vec<Box<dyn Forecaster>> = vec![Box::new(arima.model), Box::new(ets.model)];
+    for model in &mut models {
+        let bands = model.forecast_with_intervals(10, &[0.8, 0.95]).unwrap();
+        let (narrow, wide) = (bands.interval(0.8).unwrap(), bands.interval(0.95).unwrap());
+        assert!(wide.lower[9] < narrow.lower[9] && narrow.upper[9] < wide.upper[9]);
+        // Caminata: la incertidumbre crece con el horizonte
+        assert!(bands.std_error[9] > 2.0 * bands.std_error[0]);
+        assert!(model.aic() < model.bic() && model.aicc() > model.aic());
+
+        // Actualizar no cambia la verosimilitud ni los criterios; solo agrega un residuo
+        let (aic, aicc, bic) = (model.aic(), model.aicc(), model.bic());
+        let (nobs, residuals) = (model.nobs(), model.residuals().len());
+        let next = model.update(series[270]).unwrap();
+        assert_eq!((model.aic(), model.aicc(), model.bic()), (aic, aicc, bic));
+        assert_eq!(model.nobs(), nobs);
+        assert_eq!(model.residuals().len(), residuals + 1);
+        assert_eq!(model.forecast(1)[0], next);
+    }
+    assert_eq!(models[0].name(), "ARIMA(0,1,1)");
+    assert_eq!(models[1].name(), "ETS(A,Ad,N)");
+
+    // Las especificaciones tambien se ajustan sobre un Dataset
+    let data = Dataset::new(None, vec![("Level".to_string(), series.clone())]).unwrap();
+    let model = ArimaSpec::new(1, 1, 0).fit_dataset(&data, "Level").unwrap();
+    assert!((Forecaster::aic(&model) - ArimaModel::aic(&model)).abs() < 1e-9);
+    assert!(EtsSpec::simple().fit_dataset(&data, "CO2").is_err());
+}
@@ -0,0 +1,494 @@
+use super::arima::PredictionInterval;
+use super::arima_eval::{WeightingStrategy, generate_weights};
+use super::error::{Error, Result};
+use std::fmt;
+
+// Pesos validados; `None` equivale a pesos iguales. No hace falta que sumen 1.
+fn resolve_weights(n: usize, weights: Option<&[f64]>) -> Result<Vec<f64>> {
+    if n == 0 {
+        return Err(Error::Evaluation(
+            "Cannot compute metrics on empty series".into(),
+        ));
+    }
+    let weights = match weights {
+        Some(w) if w.len() != n => {
+            return Err(Error::Evaluation(format!(
+                "Expected {} weights, got {}",
+                n,
+                w.len()
+            )));
+        }
+        Some(w) => w.to_vec(),
+        None => vec![1.0; n],
+    };
+    if weights.iter().any(|w| !w.is_finite() || *w < 0.0) {
+        return Err(Error::Evaluation(
+            "Weights must be finite and non-negative".into(),
+        ));
+    }
+    if weights.iter().sum::<f64>() <= 0.0 {
+        return Err(Error::Evaluation("Weights must not all be zero".into()));
+    }
+    Ok(weights)
+}
+
+fn check_lengths(actual: &[f64], other: &[f64]) -> Result<()> {
+    if actual.len() != other.len() {
+        return Err(Error::Evaluation(format!(
+            "Actual and forecast values must have the same length ({} vs {})",
+            actual.len(),
+            other.len()
+        )));
+    }
+    Ok(())
+}
+
+// Media ponderada de los terminos definidos (`None` se descarta)
+fn weighted_mean(terms: impl Iterator<Item = Option<f64>>, weights: &[f64]) -> f64 {
+    let (sum, total) = terms
+        .zip(weights)
+        .filter_map(|(term, w)| term.map(|t| (t * w, *w)))
+        .fold((0.0, 0.0), |(s, t), (v, w)| (s + v, t + w));
+    if total > 0.0 { sum / total } else { f64::NAN }
+}
+
+// Error medio de escala de un pronostico ingenuo estacional en el entrenamiento
+fn naive_scale(training: &[f64], season: usize, power: i32) -> Result<f64> {
+    let season = season.max(1);
+    if training.len() <= season {
+        return Err(Error::Evaluation(
+            "Training series is too short for the seasonal naive scale".into(),
+        ));
+    }
+    let diffs: Vec<f64> = (season..training.len())
+        .map(|t| (training[t] - training[t - season]).abs().powi(power))
+        .collect();
+    let scale = diffs.iter().sum::<f64>() / diffs.len() as f64;
+    if scale <= 0.0 {
+        return Err(Error::Evaluation(
+            "Training series is constant; scaled errors are undefined".into(),
+        ));
+    }
+    Ok(scale)
+}
+
+/// Error medio (actual - pronostico) ponderado
+pub fn mean_error(actual: &[f64], predicted: &[f64], weights: Option<&[f64]>) -> Result<f64> {
+    check_lengths(actual, predicted)?;
+    let w = resolve_weights(actual.len(), weights)?;
+    Ok(weighted_mean(
+        actual.iter().zip(predicted).map(|(a, p)| Some(a - p)),
+        &w,
+    ))
+}
+
+/// Error absoluto medio ponderado
+pub fn mae(actual: &[f64], predicted: &[f64], weights: Option<&[f64]>) -> Result<f64> {
+    check_lengths(actual, predicted)?;
+    let w = resolve_weights(actual.len(), weights)?;
+    Ok(weighted_mean(
+        actual
+            .iter()
+            .zip(predicted)
+            .map(|(a, p)| Some((a - p).abs())),
+        &w,
+    ))
+}
+
+/// Raiz del error cuadratico medio ponderado
+pub fn rmse(actual: &[f64], predicted: &[f64], weights: Option<&[f64]>) -> Result<f64> {
+    check_lengths(actual, predicted)?;
+    let w = resolve_weights(actual.len(), weights)?;
+    Ok(weighted_mean(
+        actual
+            .iter()
+            .zip(predicted)
+            .map(|(a, p)| Some((a - p).powi(2))),
+        &w,
+    )
+    .sqrt())
+}
+
+/// Error porcentual absoluto medio; se omiten los puntos con valor real cero
+///
+/// Error si ningun punto con valor real distinto de cero tiene peso.
+pub fn mape(actual: &[f64], predicted: &[f64], weights: Option<&[f64]>) -> Result<f64> {
+    check_lengths(actual, predicted)?;
+    let w = resolve_weights(actual.len(), weights)?;
+    let value = weighted_mean(
+        actual
+            .iter()
+            .zip(predicted)
+            .map(|(a, p)| (*a != 0.0).then(|| 100.0 * ((a - p) / a).abs())),
+        &w,
+    );
+    if value.is_nan() {
+        return Err(Error::Evaluation(
+            "MAPE is undefined when all actual values are zero".into(),
+        ));
+    }
+    Ok(value)
+}
+
+/// MAPE simetrico en [0, 200]; un punto con real y pronostico en cero aporta 0
+pub fn smape(actual: &[f64], predicted: &[f64], weights: Option<&[f64]>) -> Result<f64> {
+    check_lengths(actual, predicted)?;
+    let w = resolve_weights(actual.len(), weights)?;
+    Ok(weighted_mean(
+        actual.iter().zip(predicted).map(|(a, p)| {
+            let denom = a.abs() + p.abs();
+            Some(if denom > 0.0 {
+                200.0 * (a - p).abs() / denom
+            } else {
+                0.0
+            })
+        }),
+        &w,
+    ))
+}
+
+/// Error absoluto medio escalado (Hyndman y Koehler, 2006)
+///
+/// La escala es el MAE del pronostico ingenuo estacional de periodo `season`
+/// (1 = no estacional) dentro de la serie de entrenamiento.
+pub fn mase(
+    actual: &[f64],
+    predicted: &[f64],
+    training: &[f64],
+    season: usize,
+    weights: Option<&[f64]>,
+) -> Result<f64> {
+    Ok(mae(actual, predicted, weights)? / naive_scale(training, season, 1)?)
+}
+
+/// Raiz del error cuadratico medio escalado (usado en M5)
+pub fn rmsse(
+    actual: &[f64],
+    predicted: &[f64],
+    training: &[f64],
+    season: usize,
+    weights: Option<&[f64]>,
+) -> Result<f64> {
+    let mse = rmse(actual, predicted, weights)?.powi(2);
+    Ok((mse / naive_scale(training, season, 2)?).sqrt())
+}
+
+/// Mediana ponderada del error absoluto
+pub fn median_abs_error(actual: &[f64], predicted: &[f64], weights: Option<&[f64]>) -> Result<f64> {
+    check_lengths(actual, predicted)?;
+    let w = resolve_weights(actual.len(), weights)?;
+    let mut errors: Vec<(f64, f64)> = actual
+        .iter()
+        .zip(predicted)
+        .map(|(a, p)| (a - p).abs())
+        .zip(w.iter().copied())
+        .filter(|(_, weight)| *weight > 0.0)
+        .collect();
+    errors.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    // Primer error cuyo peso acumulado alcanza la mitad; si la mitad cae
+    // justo entre dos errores se promedian (mediana usual con pesos iguales)
+    let half = w.iter().sum::<f64>() / 2.0;
+    let mut cumulative = 0.0;
+    for (i, (error, weight)) in errors.iter().enumerate() {
+        cumulative += weight;
+        if (cumulative - half).abs() <= 1e-12 * half && i + 1 < errors.len() {
+            return Ok((error + errors[i + 1].0) / 2.0);
+        }
+        if cumulative >= half {
+            return Ok(*error);
+        }
+    }
+    Ok(errors.last().map_or(f64::NAN, |e| e.0))
+}
+
+/// Sesgo porcentual: 100 * sum w (pronostico - real) / sum w |real|
+///
+/// Positivo cuando el modelo sobreestima.
+pub fn percent_bias(actual: &[f64], predicted: &[f64], weights: Option<&[f64]>) -> Result<f64> {
+    check_lengths(actual, predicted)?;
+    let w = resolve_weights(actual.len(), weights)?;
+    let excess: f64 = actual
+        .iter()
+        .zip(predicted)
+        .zip(&w)
+        .map(|((a, p), w)| w * (p - a))
+        .sum();
+    let level: f64 = actual.iter().zip(&w).map(|(a, w)| w * a.abs()).sum();
+    if level == 0.0 {
+        return Err(Error::Evaluation(
+            "Percent bias is undefined when all actual values are zero".into(),
+        ));
+    }
+    Ok(100.0 * excess / level)
+}
+
+/// Perdida pinball (cuantil) de un pronostico del cuantil `tau`
+pub fn pinball_loss(
+    actual: &[f64],
+    quantile_forecast: &[f64],
+    tau: f64,
+    weights: Option<&[f64]>,
+) -> Result<f64> {
+    if !(0.0..=1.0).contains(&tau) {
+        return Err(Error::Evaluation("Quantile level must be in [0, 1]".into()));
+    }
+    check_lengths(actual, quantile_forecast)?;
+    let w = resolve_weights(actual.len(), weights)?;
+    Ok(weighted_mean(
+        actual.iter().zip(quantile_forecast).map(|(a, q)| {
+            let diff = a - q;
+            Some(if diff >= 0.0 {
+                tau * diff
+            } else {
+                (tau - 1.0) * diff
+            })
+        }),
+        &w,
+    ))
+}
+
+/// Fraccion (ponderada) de valores reales dentro del intervalo
+pub fn interval_coverage(
+    actual: &[f64],
+    lower: &[f64],
+    upper: &[f64],
+    weights: Option<&[f64]>,
+) -> Result<f64> {
+    check_lengths(actual, lower)?;
+    check_lengths(actual, upper)?;
+    let w = resolve_weights(actual.len(), weights)?;
+    Ok(weighted_mean(
+        (0..actual.len()).map(|i| Some(((lower[i]..=upper[i]).contains(&actual[i])) as u8 as f64)),
+        &w,
+    ))
+}
+
+/// Puntaje de Winkler de un intervalo de nivel `1 - alpha`: ancho mas una
+/// penalizacion 2/alpha por la distancia de cada valor fuera del intervalo
+pub fn winkler_score(
+    actual: &[f64],
+    lower: &[f64],
+    upper: &[f64],
+    alpha: f64,
+    weights: Option<&[f64]>,
+) -> Result<f64> {
+    if !(alpha > 0.0 && alpha < 1.0) {
+        return Err(Error::Evaluation("Alpha must be in (0, 1)".into()));
+    }
+    check_lengths(actual, lower)?;
+    check_lengths(actual, upper)?;
+    let w = resolve_weights(actual.len(), weights)?;
+    Ok(weighted_mean(
+        (0..actual.len()).map(|i| {
+            let (a, l, u) = (actual[i], lower[i], upper[i]);
+            let penalty = if a < l {
+                2.0 / alpha * (l - a)
+            } else if a > u {
+                2.0 / alpha * (a - u)
+            } else {
+                0.0
+            };
+            Some(u - l + penalty)
+        }),
+        &w,
+    ))
+}
+
+/// Datos opcionales para las metricas que los necesitan
+pub struct EvaluationOptions<'a> {
+    pub weighting: WeightingStrategy,
+    pub training: Option<&'a [f64]>, // Para MASE y RMSSE
+    pub season: usize,               // Periodo del pronostico ingenuo de escala
+    pub interval: Option<&'a PredictionInterval>,
+    pub quantiles: &'a [(f64, &'a [f64])], // (tau, pronostico del cuantil)
+}
+
+impl Default for EvaluationOptions<'_> {
+    fn default() -> Self {
+        EvaluationOptions {
+            weighting: WeightingStrategy::Equal,
+            training: None,
+            season: 1,
+            interval: None,
+            quantiles: &[],
+        }
+    }
+}
+
+/// Reporte de precision; las metricas sin datos suficientes quedan en `None`
+#[derive(Debug, Clone)]
+pub struct MetricsReport {
+    pub n: usize,
+    pub me: f64,
+    pub mae: f64,
+    pub rmse: f64,
+    pub mape: Option<f64>, // Indefinido si todos los valores reales son cero
+    pub smape: f64,
+    pub median_abs_error: f64,
+    pub percent_bias: Option<f64>, // Indefinido si todos los valores reales son cero
+    pub mase: Option<f64>,
+    pub rmsse: Option<f64>,
+    pub pinball: Vec<(f64, f64)>, // (tau, perdida)
+    pub coverage: Option<f64>,
+    pub winkler: Option<f64>,
+}
+
+/// Calcula todas las metricas con los mismos pesos
+///
+/// Parameters:
+/// - actual: Los valores observados
+/// - predicted: Los pronosticos puntuales
+/// - options: estrategia de pesos y datos extra (entrenamiento, intervalo, cuantiles)
+pub fn evaluate(
+    actual: &[f64],
+    predicted: &[f64],
+    options: &EvaluationOptions,
+) -> Result<MetricsReport> {
+    check_lengths(actual, predicted)?;
+    let weights = generate_weights(actual.len(), options.weighting.clone())?;
+    let w = Some(weights.as_slice());
+
+    let (mase, rmsse) = match options.training {
+        Some(training) => (
+            Some(mase(actual, predicted, training, options.season, w)?),
+            Some(rmsse(actual, predicted, training, options.season, w)?),
+        ),
+        None => (None, None),
+    };
+    let (coverage, winkler) = match options.interval {
+        Some(interval) => (
+            Some(interval_coverage(
+                actual,
+                &interval.lower,
+                &interval.upper,
+                w,
+            )?),
+            Some(winkler_score(
+                actual,
+                &interval.lower,
+                &interval.upper,
+                1.0 - interval.level,
+                w,
+            )?),
+        ),
+        None => (None, None),
+    };
+    let pinball = options
+        .quantiles
+        .iter()
+        .map(|(tau, forecast)| Ok((*tau, pinball_loss(actual, forecast, *tau, w)?)))
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(MetricsReport {
+        n: actual.len(),
+        me: mean_error(actual, predicted, w)?,
+        mae: mae(actual, predicted, w)?,
+        rmse: rmse(actual, predicted, w)?,
+        mape: mape(actual, predicted, w).ok(),
+        smape: smape(actual, predicted, w)?,
+        median_abs_error: median_abs_error(actual, predicted, w)?,
+        percent_bias: percent_bias(actual, predicted, w).ok(),
+        mase,
+        rmsse,
+        pinball,
+        coverage,
+        winkler,
+    })
+}
+
+impl fmt::Display for MetricsReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Puntos evaluados: {}", self.n)?;
+        writeln!(f, "Error medio (ME): {:.4}", self.me)?;
+        writeln!(f, "Error absoluto medio (MAE): {:.4}", self.mae)?;
+        writeln!(
+            f,
+            "Raíz del error cuadrático medio (RMSE): {:.4}",
+            self.rmse
+        )?;
+        if let Some(mape) = self.mape {
+            writeln!(f, "MAPE: {:.4}%", mape)?;
+        }
+        writeln!(f, "sMAPE: {:.4}%", self.smape)?;
+        writeln!(
+            f,
+            "Mediana del error absoluto: {:.4}",
+            self.median_abs_error
+        )?;
+        if let Some(percent_bias) = self.percent_bias {
+            writeln!(f, "Sesgo porcentual: {:.4}%", percent_bias)?;
+        }
+        if let (Some(mase), Some(rmsse)) = (self.mase, self.rmsse) {
+            writeln!(f, "MASE: {:.4}", mase)?;
+            writeln!(f, "RMSSE: {:.4}", rmsse)?;
+        }
+        for (tau, loss) in &self.pinball {
+            writeln!(f, "Pérdida pinball (tau={}): {:.4}", tau, loss)?;
+        }
+        if let (Some(coverage), Some(winkler)) = (self.coverage, self.winkler) {
+            writeln!(f, "Cobertura del intervalo: {:.2}%", 100.0 * coverage)?;
+            writeln!(f, "Puntaje de Winkler: {:.4}", winkler)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_metrics_reference_values() {
+    let actual = [10.0, 12.0, 8.0, 0.0];
+    let predicted = [11.0, 12.0, 6.0, 1.0];
+    let training = [1.0, 3.0, 2.0, 4.0];
+
+    let report = evaluate(
+        &actual,
+        &predicted,
+        &EvaluationOptions {
+            training: Some(&training),
+            ..EvaluationOptions::default()
+        },
+    )
+    .unwrap();
+    assert!((report.me - 0.0).abs() < 1e-12);
+    assert!((report.mae - 1.0).abs() < 1e-12);
+    assert!((report.rmse - 1.5f64.sqrt()).abs() < 1e-12);
+    assert!((report.mape.unwrap() - 35.0 / 3.0).abs() < 1e-12); // (10% + 0% + 25%) / 3, sin el cero
+    assert!((report.median_abs_error - 1.0).abs() < 1e-12);
+    assert!((report.percent_bias.unwrap() - 0.0).abs() < 1e-12);
+    // Escala ingenua del entrenamiento: MAE 5/3, MSE 3
+    assert!((report.mase.unwrap() - 0.6).abs() < 1e-12);
+    assert!((report.rmsse.unwrap() - 0.5f64.sqrt()).abs() < 1e-12);
+
+    // Pesos: solo el ultimo punto cuenta
+    let weights = [0.0, 0.0, 0.0, 2.0];
+    assert_eq!(mae(&actual, &predicted, Some(&weights)).unwrap(), 1.0);
+    assert_eq!(smape(&actual, &predicted, Some(&weights)).unwrap(), 200.0);
+    assert!(mape(&actual, &predicted, Some(&weights)).is_err());
+
+    // Sin valores reales distintos de cero el MAPE y el sesgo quedan indefinidos
+    let zeros = evaluate(&[0.0; 3], &[1.0, 2.0, 3.0], &EvaluationOptions::default()).unwrap();
+    assert_eq!((zeros.mape, zeros.percent_bias), (None, None));
+    assert!((zeros.smape - 200.0).abs() < 1e-12);
+    assert!(!zeros.to_string().lines().any(|l| l.starts_with("MAPE")));
+
+    assert_eq!(
+        pinball_loss(&[1.0, 3.0], &[2.0, 2.0], 0.9, None).unwrap(),
+        0.5
+    );
+    let (lower, upper) = ([0.0, 0.0], [1.0, 1.0]);
+    assert_eq!(
+        interval_coverage(&[0.5, 2.0], &lower, &upper, None).unwrap(),
+        0.5
+    );
+    // Ancho 1 y penalizacion 2 / 0.1 * 1 = 20 en el segundo punto
+    assert_eq!(
+        winkler_score(&[0.5, 2.0], &lower, &upper, 0.1, None).unwrap(),
+        11.0
+    );
+    assert!(mae(&actual, &predicted[..2], None).is_err());
+
+    // Con todos los reales en cero el sesgo porcentual queda indefinido, no el reporte
+    let zeros = evaluate(&[0.0, 0.0], &[1.0, -1.0], &EvaluationOptions::default()).unwrap();
+    assert_eq!(zeros.percent_bias, None);
+    assert_eq!(zeros.mae, 1.0);
+}
@@ -1,5 +1,45 @@
+#[cfg(feature = "std")]
 pub mod arima;
 
+#[cfg(feature = "std")]
 pub mod arima_eval;
+#[cfg(feature = "std")]
+pub mod auto_arima;
+#[cfg(feature = "std")]
+pub mod backtest;
+#[cfg(feature = "std")]
+pub mod codegen;
+#[cfg(feature = "std")]
 pub mod data_struct;
+#[cfg(feature = "std")]
+pub mod diagnostics;
+pub mod embedded;
+#[cfg(feature = "std")]
+pub mod ensemble;
+#[cfg(feature = "std")]
+pub mod error;
+#[cfg(feature = "std")]
+pub mod ets;
+pub mod fixed_point;
+#[cfg(feature = "std")]
+pub mod forecaster;
+#[cfg(feature = "std")]
+pub mod metrics;
+#[cfg(feature = "std")]
+pub mod optim;
+#[cfg(feature = "std")]
+pub mod outliers;
+#[cfg(feature = "std")]
+pub mod persistence;
+#[cfg(feature = "std")]
+pub mod resample;
+#[cfg(feature = "std")]
 pub mod sa;
+#[cfg(feature = "std")]
+pub mod significance;
+#[cfg(feature = "std")]
+pub mod stats;
+#[cfg(test)]
+mod test_util;
+#[cfg(feature = "std")]
+pub mod time_index;
@@ -0,0 +1,166 @@
+/// Opciones del metodo simplex de Nelder-Mead
+#[derive(Debug, Clone)]
+pub struct NelderMeadOptions {
+    pub max_iterations: usize,
+    pub tolerance: f64,    // Diferencia maxima de la funcion dentro del simplex
+    pub initial_step: f64, // Tamano relativo del simplex inicial
+}
+
+impl Default for NelderMeadOptions {
+    fn default() -> Self {
+        NelderMeadOptions {
+            max_iterations: 2000,
+            tolerance: 1e-8,
+            initial_step: 0.1,
+        }
+    }
+}
+
+/// Resultado de una minimizacion
+#[derive(Debug, Clone)]
+pub struct OptimResult {
+    pub x: Vec<f64>,
+    pub value: f64,
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+/// Minimiza `f` sin derivadas con el metodo de Nelder-Mead
+///
+/// Parameters:
+/// - f: funcion objetivo; valores no finitos se tratan como +infinito
+/// - x0: punto inicial
+/// - options: criterios de parada y tamano del simplex inicial
+///
+/// Returns:
+/// - El mejor punto encontrado y su valor
+pub fn nelder_mead<F>(mut f: F, x0: &[f64], options: &NelderMeadOptions) -> OptimResult
+where
+    F: FnMut(&[f64]) -> f64,
+{
+    let n = x0.len();
+    let mut eval = |x: &[f64]| {
+        let value = f(x);
+        if value.is_finite() {
+            value
+        } else {
+            f64::INFINITY
+        }
+    };
+
+    if n == 0 {
+        return OptimResult {
+            x: Vec::new(),
+            value: eval(x0),
+            iterations: 0,
+            converged: true,
+        };
+    }
+
+    // Simplex inicial: x0 mas un paso en cada direccion
+    let mut simplex: Vec<Vec<f64>> = vec![x0.to_vec()];
+    for i in 0..n {
+        let mut vertex = x0.to_vec();
+        let step = if vertex[i].abs() > 1e-8 {
+            options.initial_step * vertex[i].abs()
+        } else {
+            options.initial_step
+        };
+        vertex[i] += step;
+        simplex.push(vertex);
+    }
+    let mut values: Vec<f64> = simplex.iter().map(|v| eval(v)).collect();
+
+    let (alpha, gamma, rho, sigma) = (1.0, 2.0, 0.5, 0.5);
+    let mut iterations = 0;
+    let mut converged = false;
+
+    while iterations < options.max_iterations {
+        iterations += 1;
+
+        // Ordena los vertices del mejor al peor
+        let mut order: Vec<usize> = (0..=n).collect();
+        order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+        simplex = order.iter().map(|&i| simplex[i].clone()).collect();
+        values = order.iter().map(|&i| values[i]).collect();
+
+        if (values[n] - values[0]).abs() <= options.tolerance * (1.0 + values[0].abs()) {
+            converged = true;
+            break;
+        }
+
+        // Centroide de todos menos el peor
+        let mut centroid = vec![0.0; n];
+        for vertex in &simplex[..n] {
+            for (c, v) in centroid.iter_mut().zip(vertex) {
+                *c += v / n as f64;
+            }
+        }
+        let towards = |coef: f64| -> Vec<f64> {
+            centroid
+                .iter()
+                .zip(&simplex[n])
+                .map(|(c, w)| c + coef * (w - c))
+                .collect()
+        };
+
+        let reflected = towards(-alpha);
+        let reflected_value = eval(&reflected);
+
+        if reflected_value < values[0] {
+            let expanded = towards(-gamma);
+            let expanded_value = eval(&expanded);
+            if expanded_value < reflected_value {
+                simplex[n] = expanded;
+                values[n] = expanded_value;
+            } else {
+                simplex[n] = reflected;
+                values[n] = reflected_value;
+            }
+        } else if reflected_value < values[n - 1] {
+            simplex[n] = reflected;
+            values[n] = reflected_value;
+        } else {
+            let contracted = towards(rho);
+            let contracted_value = eval(&contracted);
+            if contracted_value < values[n] {
+                simplex[n] = contracted;
+                values[n] = contracted_value;
+            } else {
+                // Encoge todo el simplex hacia el mejor vertice
+                for i in 1..=n {
+                    let shrunk: Vec<f64> = simplex[0]
+                        .iter()
+                        .zip(&simplex[i])
+                        .map(|(b, v)| b + sigma * (v - b))
+                        .collect();
+                    values[i] = eval(&shrunk);
+                    simplex[i] = shrunk;
+                }
+            }
+        }
+    }
+
+    let best = (0..=n)
+        .min_by(|&a, &b| values[a].total_cmp(&values[b]))
+        .unwrap_or(0);
+    OptimResult {
+        x: simplex[best].clone(),
+        value: values[best],
+        iterations,
+        converged,
+    }
+}
+
+#[test]
+fn test_nelder_mead_rosenbrock() {
+    let rosenbrock = |x: &[f64]| (1.0 - x[0]).powi(2) + 100.0 * (x[1] - x[0].powi(2)).powi(2);
+    let options = NelderMeadOptions {
+        tolerance: 1e-14,
+        ..NelderMeadOptions::default()
+    };
+    let result = nelder_mead(rosenbrock, &[-1.2, 1.0], &options);
+    assert!(result.converged);
+    assert!((result.x[0] - 1.0).abs() < 1e-3);
+    assert!((result.x[1] - 1.0).abs() < 1e-3);
+}
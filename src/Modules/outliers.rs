@@ -0,0 +1,241 @@
+use super::resample::{FillMethod, fill_gaps};
+
+// Factor que convierte la MAD en una estimacion de la desviacion estandar
+const MAD_SCALE: f64 = 1.4826;
+
+/// Resultado de un detector: indices marcados como sospechosos
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Detection {
+    pub flagged: Vec<usize>,
+}
+
+impl Detection {
+    fn from_mask(mask: &[bool]) -> Detection {
+        Detection {
+            flagged: (0..mask.len()).filter(|&i| mask[i]).collect(),
+        }
+    }
+
+    pub fn is_flagged(&self, index: usize) -> bool {
+        self.flagged.binary_search(&index).is_ok()
+    }
+
+    /// Union de dos detecciones, util para encadenar detectores
+    pub fn merge(mut self, other: &Detection) -> Detection {
+        self.flagged.extend_from_slice(&other.flagged);
+        self.flagged.sort_unstable();
+        self.flagged.dedup();
+        self
+    }
+
+    /// Serie limpia: los puntos marcados se reemplazan por interpolacion
+    /// lineal, lista para `ArimaModel::fit`
+    pub fn cleaned(&self, series: &[f64]) -> Vec<f64> {
+        let mut values = series.to_vec();
+        for &i in &self.flagged {
+            if i < values.len() {
+                values[i] = f64::NAN;
+            }
+        }
+        match fill_gaps(&values, FillMethod::Linear) {
+            Ok((filled, _)) => filled,
+            Err(_) => series.to_vec(), // Todo marcado: no hay de donde interpolar
+        }
+    }
+}
+
+/// Resolucion del sensor: el menor salto distinto de cero entre muestras
+///
+/// Un DHT reporta en pasos de 0.1, asi que la dispersion nunca se considera
+/// menor a ese paso y las mesetas de cuantizacion no disparan falsos positivos
+pub fn quantization_step(series: &[f64]) -> Option<f64> {
+    series
+        .windows(2)
+        .map(|w| (w[1] - w[0]).abs())
+        .filter(|d| *d > 1e-9)
+        .min_by(|a, b| a.total_cmp(b))
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_by(|a, b| a.total_cmp(b));
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+// Mediana y dispersion robusta (MAD escalada) de una ventana sin NaN
+fn robust_center(window: &[f64]) -> Option<(f64, f64)> {
+    let mut values: Vec<f64> = window.iter().copied().filter(|v| !v.is_nan()).collect();
+    if values.is_empty() {
+        return None;
+    }
+    let center = median(&mut values);
+    let mut deviations: Vec<f64> = values.iter().map(|v| (v - center).abs()).collect();
+    Some((center, MAD_SCALE * median(&mut deviations)))
+}
+
+/// Filtro de Hampel con ventana centrada de `2 * half_window + 1` puntos
+///
+/// Marca los puntos que se alejan de la mediana local mas de `n_sigmas`
+/// desviaciones robustas
+pub fn hampel(series: &[f64], half_window: usize, n_sigmas: f64) -> Detection {
+    let floor = quantization_step(series).unwrap_or(0.0);
+    let mask: Vec<bool> = (0..series.len())
+        .map(|i| {
+            let start = i.saturating_sub(half_window);
+            let end = (i + half_window + 1).min(series.len());
+            match robust_center(&series[start..end]) {
+                Some((center, scale)) if !series[i].is_nan() => {
+                    (series[i] - center).abs() > n_sigmas * scale.max(floor)
+                }
+                _ => false,
+            }
+        })
+        .collect();
+    Detection::from_mask(&mask)
+}
+
+/// Z-score sobre una ventana movil de los `window` puntos anteriores
+///
+/// Solo usa el pasado, asi que se puede aplicar igual en linea en el gateway
+pub fn rolling_zscore(series: &[f64], window: usize, threshold: f64) -> Detection {
+    let floor = quantization_step(series).unwrap_or(0.0);
+    let mask: Vec<bool> = (0..series.len())
+        .map(|i| {
+            if i < window || series[i].is_nan() {
+                return false;
+            }
+            let past: Vec<f64> = series[i - window..i]
+                .iter()
+                .copied()
+                .filter(|v| !v.is_nan())
+                .collect();
+            if past.len() < 2 {
+                return false;
+            }
+            let mean = past.iter().sum::<f64>() / past.len() as f64;
+            let variance =
+                past.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (past.len() - 1) as f64;
+            (series[i] - mean).abs() > threshold * variance.sqrt().max(floor)
+        })
+        .collect();
+    Detection::from_mask(&mask)
+}
+
+/// Variante robusta del z-score: mediana y MAD de los `window` puntos anteriores
+pub fn rolling_mad(series: &[f64], window: usize, threshold: f64) -> Detection {
+    let floor = quantization_step(series).unwrap_or(0.0);
+    let mask: Vec<bool> = (0..series.len())
+        .map(|i| {
+            if i < window || series[i].is_nan() {
+                return false;
+            }
+            match robust_center(&series[i - window..i]) {
+                Some((center, scale)) => (series[i] - center).abs() > threshold * scale.max(floor),
+                None => false,
+            }
+        })
+        .collect();
+    Detection::from_mask(&mask)
+}
+
+/// Sensor trabado: corridas de al menos `min_run` lecturas que no cambian
+/// mas de `tolerance`
+///
+/// `min_run` debe ser mayor que las mesetas normales de cuantizacion (a 10 s
+/// por muestra, la temperatura puede quedarse quieta varios minutos). Se
+/// usa al menos 2: una lectura sola no es una corrida y con menos se
+/// marcaria toda la serie.
+pub fn stuck_values(series: &[f64], min_run: usize, tolerance: f64) -> Detection {
+    let min_run = min_run.max(2);
+    let mut mask = vec![false; series.len()];
+    let mut run_start = 0;
+
+    for i in 1..=series.len() {
+        let continues = i < series.len() && (series[i] - series[run_start]).abs() <= tolerance;
+        if !continues {
+            if i - run_start >= min_run {
+                mask[run_start..i].iter_mut().for_each(|m| *m = true);
+            }
+            run_start = i;
+        }
+    }
+    Detection::from_mask(&mask)
+}
+
+/// Cambios fisicamente imposibles entre muestras consecutivas
+///
+/// Se compara contra el ultimo valor aceptado para que un pico no marque
+/// tambien la muestra buena que le sigue. Si `confirm` muestras rechazadas
+/// seguidas concuerdan entre si (saltos de a lo sumo `max_delta`), se trata
+/// de un cambio de nivel real: se desmarcan y el nuevo nivel pasa a ser la
+/// referencia. `confirm` se lleva a al menos 2; con 0 o 1 cada salto se
+/// aceptaria como nivel nuevo y no se marcaria nada.
+pub fn rate_of_change(series: &[f64], max_delta: f64, confirm: usize) -> Detection {
+    let confirm = confirm.max(2);
+    let mut mask = vec![false; series.len()];
+    let mut last_good: Option<f64> = None;
+    let mut run: Vec<usize> = Vec::new(); // Rechazadas consecutivas que concuerdan
+
+    for (i, &value) in series.iter().enumerate() {
+        if value.is_nan() {
+            continue;
+        }
+        match last_good {
+            Some(previous) if (value - previous).abs() > max_delta => {
+                if run
+                    .last()
+                    .is_some_and(|&j| (value - series[j]).abs() > max_delta)
+                {
+                    run.clear();
+                }
+                run.push(i);
+                mask[i] = true;
+                if run.len() >= confirm {
+                    run.drain(..).for_each(|j| mask[j] = false);
+                    last_good = Some(value);
+                }
+            }
+            _ => {
+                run.clear();
+                last_good = Some(value);
+            }
+        }
+    }
+    Detection::from_mask(&mask)
+}
+
+#[test]
+fn test_detectors_flag_spike_and_stuck_run() {
+    let mut series: Vec<f64> = (0..40).map(|i| 27.0 + 0.1 * ((i / 4) % 3) as f64).collect();
+    series[20] = 45.0;
+
+    let spike = hampel(&series, 5, 3.0);
+    assert_eq!(spike.flagged, vec![20]);
+    assert_eq!(rate_of_change(&series, 2.0, 3).flagged, vec![20]);
+    for confirm in [0, 1] {
+        assert_eq!(rate_of_change(&series, 2.0, confirm).flagged, vec![20]);
+    }
+
+    // Escalon real de +10: tres muestras que concuerdan fijan el nuevo nivel
+    let mut step = series.clone();
+    step[20] = series[20 - 1];
+    step[25..].iter_mut().for_each(|v| *v += 10.0);
+    assert_eq!(rate_of_change(&step, 2.0, 3).flagged, Vec::<usize>::new());
+    step[30] = -20.0;
+    assert_eq!(rate_of_change(&step, 2.0, 3).flagged, vec![30]);
+
+    let cleaned = spike.cleaned(&series);
+    assert!((cleaned[20] - (series[19] + series[21]) / 2.0).abs() < 1e-12);
+
+    let stuck = stuck_values(&[1.0, 2.0, 2.0, 2.0, 2.0, 3.0], 4, 1e-9);
+    assert_eq!(stuck.flagged, vec![1, 2, 3, 4]);
+    // Con min_run 0 o 1 se exige igual una corrida de dos lecturas
+    for min_run in [0, 1] {
+        let stuck = stuck_values(&[1.0, 2.0, 2.0, 3.0, 4.0], min_run, 1e-9);
+        assert_eq!(stuck.flagged, vec![1, 2]);
+    }
+}
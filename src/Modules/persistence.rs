@@ -0,0 +1,141 @@
+use super::arima::ArimaModel;
+use super::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+// Version del formato guardado; se incrementa si cambia la estructura del modelo
+const FORMAT_VERSION: u32 = 1;
+
+// Cabecera del formato binario para reconocer archivos ajenos o corruptos
+const BINARY_MAGIC: &[u8; 4] = b"ARIM";
+
+#[derive(Serialize)]
+struct SavedModelRef<'a> {
+    version: u32,
+    model: &'a ArimaModel,
+}
+
+#[derive(Deserialize)]
+struct SavedModel {
+    version: u32,
+    model: ArimaModel,
+}
+
+fn check_version(version: u32) -> Result<()> {
+    if version != FORMAT_VERSION {
+        return Err(Error::Serialization(format!(
+            "Unsupported model format version {} (expected {})",
+            version, FORMAT_VERSION
+        )));
+    }
+    Ok(())
+}
+
+impl ArimaModel {
+    /// Serializa el modelo ajustado a JSON legible
+    ///
+    /// Incluye coeficientes, media, estado de diferenciacion y solo la cola
+    /// reciente de residuos, asi que no depende del tamano del entrenamiento
+    pub fn to_json(&self) -> Result<String> {
+        let saved = SavedModelRef {
+            version: FORMAT_VERSION,
+            model: self,
+        };
+        Ok(serde_json::to_string_pretty(&saved)?)
+    }
+
+    /// Reconstruye un modelo desde JSON sin necesitar los datos originales
+    pub fn from_json(json: &str) -> Result<ArimaModel> {
+        let saved: SavedModel = serde_json::from_str(json)?;
+        check_version(saved.version)?;
+        saved.model.check_state()?;
+        Ok(saved.model)
+    }
+
+    /// Formato binario compacto (postcard) precedido por "ARIM"
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let saved = SavedModelRef {
+            version: FORMAT_VERSION,
+            model: self,
+        };
+        let mut bytes = BINARY_MAGIC.to_vec();
+        bytes.extend(postcard::to_allocvec(&saved)?);
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<ArimaModel> {
+        let payload = bytes.strip_prefix(BINARY_MAGIC.as_slice()).ok_or_else(|| {
+            Error::Serialization("Not a serialized ArimaModel (missing ARIM header)".into())
+        })?;
+        let saved: SavedModel = postcard::from_bytes(payload)?;
+        check_version(saved.version)?;
+        saved.model.check_state()?;
+        Ok(saved.model)
+    }
+
+    /// Guarda el modelo en un archivo JSON
+    pub fn save_json(&self, filepath: &str) -> Result<()> {
+        fs::write(filepath, self.to_json()?)?;
+        Ok(())
+    }
+
+    pub fn load_json(filepath: &str) -> Result<ArimaModel> {
+        ArimaModel::from_json(&fs::read_to_string(filepath)?)
+    }
+
+    /// Guarda el modelo en el formato binario compacto
+    pub fn save_binary(&self, filepath: &str) -> Result<()> {
+        fs::write(filepath, self.to_bytes()?)?;
+        Ok(())
+    }
+
+    pub fn load_binary(filepath: &str) -> Result<ArimaModel> {
+        ArimaModel::from_bytes(&fs::read(filepath)?)
+    }
+}
+
+#[test]
+fn test_model_roundtrip_json_and_binary() {
+    use super::arima::ArimaSpec;
+
+    let series: Vec<f64> = (0..300)
+        .map(|t| 25.0 + (t as f64 / 10.0).sin() + 0.01 * t as f64)
+        .collect();
+    let model = ArimaModel::fit_spec(&series, &ArimaSpec::new(2, 1, 1)).unwrap();
+    let expected = model.forecast(5);
+
+    let from_json = ArimaModel::from_json(&model.to_json().unwrap()).unwrap();
+    assert_eq!(from_json.forecast(5), expected);
+
+    let bytes = model.to_bytes().unwrap();
+    let mut from_bytes = ArimaModel::from_bytes(&bytes).unwrap();
+    assert_eq!(from_bytes.forecast(5), expected);
+    assert_eq!(from_bytes.aic(), model.aic());
+
+    // El modelo recargado sigue aceptando observaciones nuevas
+    let mut original = model.clone();
+    assert_eq!(
+        from_bytes.update(28.0).unwrap(),
+        original.update(28.0).unwrap()
+    );
+    assert!(ArimaModel::from_bytes(&bytes[4..]).is_err());
+
+    // Un archivo editado con buffers que no calzan con los ordenes se rechaza
+    let mut rls = model.clone();
+    rls.enable_rls(0.99).unwrap();
+    let json = rls.to_json().unwrap();
+    let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    value["model"]["rls"]["covariance"]
+        .as_array_mut()
+        .unwrap()
+        .pop();
+    let err = ArimaModel::from_json(&value.to_string()).unwrap_err();
+    assert!(matches!(err, Error::Serialization(_)));
+
+    let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    value["model"]["origin"]["values"] = serde_json::json!([]);
+    assert!(matches!(
+        ArimaModel::from_json(&value.to_string()),
+        Err(Error::Serialization(_))
+    ));
+}
@@ -0,0 +1,228 @@
+use super::data_struct::{Dataset, extract_series};
+use super::error::{Error, Result};
+use super::time_index::TimeIndex;
+
+/// Como se combinan varias muestras que caen en el mismo intervalo
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Aggregation {
+    Mean,   // Promedio de las muestras del intervalo
+    Last,   // Ultima muestra del intervalo
+    Median, // Mediana, robusta a picos aislados
+}
+
+/// Como se rellenan los intervalos sin muestras
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FillMethod {
+    Linear,          // Interpolacion lineal entre vecinos validos
+    ForwardFill,     // Repite el ultimo valor valido
+    Seasonal(usize), // Toma el valor de una temporada atras (en intervalos)
+}
+
+/// Serie regularizada a un intervalo fijo
+#[derive(Debug, Clone)]
+pub struct ResampledSeries {
+    pub interval_ms: u64,
+    pub times_ms: Vec<u64>, // Inicio de cada intervalo en tiempo transcurrido
+    pub values: Vec<f64>,
+    pub imputed: Vec<bool>, // `true` donde el valor fue rellenado
+}
+
+impl ResampledSeries {
+    /// Numero de puntos que no venian en los datos y fueron imputados
+    pub fn imputed_count(&self) -> usize {
+        self.imputed.iter().filter(|&&m| m).count()
+    }
+}
+
+/// Agrupa la serie en intervalos de `interval_ms` sobre el tiempo real
+///
+/// Los intervalos vacios (o con solo valores NaN) quedan como NaN para que
+/// `fill_gaps` decida como completarlos
+pub fn resample(
+    index: &TimeIndex,
+    series: &[f64],
+    interval_ms: u64,
+    aggregation: Aggregation,
+) -> Result<Vec<f64>> {
+    if index.len() != series.len() {
+        return Err(Error::Data(
+            "Time index and series must have the same length".into(),
+        ));
+    }
+    if interval_ms == 0 {
+        return Err(Error::Data(
+            "Resampling interval must be greater than zero".into(),
+        ));
+    }
+
+    let elapsed = index.elapsed_ms();
+    let bins = match elapsed.last() {
+        Some(&last) => (last / interval_ms) as usize + 1,
+        None => return Ok(Vec::new()),
+    };
+
+    let mut buckets: Vec<Vec<f64>> = vec![Vec::new(); bins];
+    for (&t, &value) in elapsed.iter().zip(series) {
+        if !value.is_nan() {
+            buckets[(t / interval_ms) as usize].push(value);
+        }
+    }
+
+    Ok(buckets
+        .into_iter()
+        .map(|bucket| aggregate(bucket, aggregation))
+        .collect())
+}
+
+fn aggregate(mut bucket: Vec<f64>, aggregation: Aggregation) -> f64 {
+    if bucket.is_empty() {
+        return f64::NAN;
+    }
+    match aggregation {
+        Aggregation::Mean => bucket.iter().sum::<f64>() / bucket.len() as f64,
+        Aggregation::Last => bucket[bucket.len() - 1],
+        Aggregation::Median => {
+            bucket.sort_by(|a, b| a.total_cmp(b));
+            let mid = bucket.len() / 2;
+            if bucket.len().is_multiple_of(2) {
+                (bucket[mid - 1] + bucket[mid]) / 2.0
+            } else {
+                bucket[mid]
+            }
+        }
+    }
+}
+
+/// Rellena los NaN de una serie regular
+///
+/// Returns:
+/// - Los valores completos y una mascara con los puntos imputados
+pub fn fill_gaps(values: &[f64], method: FillMethod) -> Result<(Vec<f64>, Vec<bool>)> {
+    let imputed: Vec<bool> = values.iter().map(|v| v.is_nan()).collect();
+    if !imputed.is_empty() && imputed.iter().all(|&m| m) {
+        return Err(Error::Data(
+            "Series has no valid values to fill from".into(),
+        ));
+    }
+
+    let filled = match method {
+        FillMethod::Linear => linear_fill(values),
+        FillMethod::ForwardFill => forward_fill(values),
+        FillMethod::Seasonal(period) => {
+            if period == 0 {
+                return Err(Error::Data(
+                    "Seasonal period must be greater than zero".into(),
+                ));
+            }
+            // Donde no hay temporada anterior se usa la interpolacion lineal
+            let fallback = linear_fill(values);
+            let mut out = values.to_vec();
+            for i in 0..out.len() {
+                if out[i].is_nan() {
+                    out[i] = if i >= period && !out[i - period].is_nan() {
+                        out[i - period]
+                    } else {
+                        fallback[i]
+                    };
+                }
+            }
+            out
+        }
+    };
+
+    Ok((filled, imputed))
+}
+
+fn linear_fill(values: &[f64]) -> Vec<f64> {
+    let mut out = values.to_vec();
+    let valid: Vec<usize> = (0..values.len()).filter(|&i| !values[i].is_nan()).collect();
+    let (first, last) = match (valid.first(), valid.last()) {
+        (Some(&f), Some(&l)) => (f, l),
+        _ => return out,
+    };
+
+    // Los extremos se extienden con el valor valido mas cercano
+    for value in out.iter_mut().take(first) {
+        *value = values[first];
+    }
+    for value in out.iter_mut().skip(last + 1) {
+        *value = values[last];
+    }
+
+    for pair in valid.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        let slope = (values[b] - values[a]) / (b - a) as f64;
+        for (k, value) in out.iter_mut().enumerate().take(b).skip(a + 1) {
+            *value = values[a] + slope * (k - a) as f64;
+        }
+    }
+    out
+}
+
+fn forward_fill(values: &[f64]) -> Vec<f64> {
+    let first_valid = values.iter().copied().find(|v| !v.is_nan());
+    let mut last = first_valid.unwrap_or(f64::NAN);
+    values
+        .iter()
+        .map(|&v| {
+            if !v.is_nan() {
+                last = v;
+            }
+            last
+        })
+        .collect()
+}
+
+/// Regulariza una serie: agrupa en intervalos fijos y rellena los huecos
+pub fn regularize(
+    index: &TimeIndex,
+    series: &[f64],
+    interval_ms: u64,
+    aggregation: Aggregation,
+    fill: FillMethod,
+) -> Result<ResampledSeries> {
+    let binned = resample(index, series, interval_ms, aggregation)?;
+    let (values, imputed) = fill_gaps(&binned, fill)?;
+    let times_ms = (0..values.len() as u64).map(|k| k * interval_ms).collect();
+
+    Ok(ResampledSeries {
+        interval_ms,
+        times_ms,
+        values,
+        imputed,
+    })
+}
+
+/// Atajo para regularizar directamente una columna de un dataset
+pub fn regularize_column(
+    data: &Dataset,
+    field: &str,
+    interval_ms: u64,
+    aggregation: Aggregation,
+    fill: FillMethod,
+) -> Result<ResampledSeries> {
+    let index = TimeIndex::from_dataset(data)?;
+    let series = extract_series(data, field)?;
+    regularize(&index, &series, interval_ms, aggregation, fill)
+}
+
+#[test]
+fn test_regularize_fills_missing_interval() {
+    let index = TimeIndex::from_millis(&[0, 10_100, 19_900, 40_050, 50_000]).unwrap();
+    let series = [1.0, 2.0, 3.0, 5.0, 6.0];
+    let result = regularize(
+        &index,
+        &series,
+        10_000,
+        Aggregation::Mean,
+        FillMethod::Linear,
+    )
+    .unwrap();
+
+    // 19_900 cae en el intervalo 1 junto con 10_100; el intervalo 2 y 3 quedan
+    // vacios y se interpolan entre 2.5 y 5.0
+    assert_eq!(result.values.len(), 6);
+    assert_eq!(result.values[1], 2.5);
+    assert_eq!(result.imputed_count(), 2);
+    assert!((result.values[2] - 3.333_333).abs() < 1e-5);
+}
@@ -0,0 +1,334 @@
+use super::error::{Error, Result};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use statrs::distribution::{ContinuousCDF, StudentsT};
+
+/// Funcion de perdida aplicada a cada error de pronostico
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LossFunction {
+    Squared,
+    Absolute,
+}
+
+impl LossFunction {
+    pub fn apply(&self, error: f64) -> f64 {
+        match self {
+            LossFunction::Squared => error * error,
+            LossFunction::Absolute => error.abs(),
+        }
+    }
+}
+
+/// Hipotesis alternativa de la prueba de Diebold-Mariano
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Alternative {
+    TwoSided, // Los modelos difieren en precision
+    Less,     // El modelo A es mas preciso (menor perdida)
+    Greater,  // El modelo B es mas preciso
+}
+
+/// Resultado de la prueba de Diebold-Mariano
+#[derive(Debug, Clone)]
+pub struct DieboldMarianoResult {
+    pub statistic: f64,       // Con la correccion de Harvey, Leybourne y Newbold
+    pub p_value: f64,         // t de Student con n - 1 grados de libertad
+    pub mean_difference: f64, // Media de L(e_a) - L(e_b); negativa favorece a A
+    pub horizon: usize,
+    pub n: usize,
+}
+
+// Autocovarianza muestral de rezago `lag` (divisor n)
+fn autocovariance(values: &[f64], mean: f64, lag: usize) -> f64 {
+    let n = values.len();
+    (lag..n)
+        .map(|t| (values[t] - mean) * (values[t - lag] - mean))
+        .sum::<f64>()
+        / n as f64
+}
+
+/// Prueba de Diebold-Mariano (1995) con la correccion de muestra pequena de
+/// Harvey, Leybourne y Newbold (1997)
+///
+/// Parameters:
+/// - errors_a, errors_b: errores (real - pronostico) de ambos modelos en los mismos puntos
+/// - horizon: horizonte de los pronosticos; fija cuantas autocovarianzas entran en la varianza
+/// - loss: funcion de perdida de la comparacion
+/// - alternative: direccion de la hipotesis alternativa
+///
+/// Returns:
+/// - El estadistico corregido y su p-valor; H0: igual precision esperada
+pub fn diebold_mariano(
+    errors_a: &[f64],
+    errors_b: &[f64],
+    horizon: usize,
+    loss: LossFunction,
+    alternative: Alternative,
+) -> Result<DieboldMarianoResult> {
+    if errors_a.len() != errors_b.len() {
+        return Err(Error::Evaluation(
+            "Both error series must have the same length".into(),
+        ));
+    }
+    let n = errors_a.len();
+    if horizon == 0 || n < horizon + 2 {
+        return Err(Error::Evaluation(
+            "Not enough forecast errors for the Diebold-Mariano test".into(),
+        ));
+    }
+
+    let differential: Vec<f64> = errors_a
+        .iter()
+        .zip(errors_b)
+        .map(|(a, b)| loss.apply(*a) - loss.apply(*b))
+        .collect();
+    let mean = differential.iter().sum::<f64>() / n as f64;
+
+    // Varianza de largo plazo con las h - 1 autocovarianzas de un pronostico a h pasos
+    let mut variance = autocovariance(&differential, mean, 0);
+    for lag in 1..horizon {
+        variance += 2.0 * autocovariance(&differential, mean, lag);
+    }
+    if variance <= 0.0 {
+        // Puede ser negativa con rezagos largos: se usa solo la varianza
+        variance = autocovariance(&differential, mean, 0);
+    }
+    if variance <= 0.0 {
+        return Err(Error::Evaluation(
+            "Loss differential is constant; the test is undefined".into(),
+        ));
+    }
+
+    let dm = mean / (variance / n as f64).sqrt();
+    let (nf, h) = (n as f64, horizon as f64);
+    let correction = ((nf + 1.0 - 2.0 * h + h * (h - 1.0) / nf) / nf).sqrt();
+    let statistic = dm * correction;
+
+    let t = StudentsT::new(0.0, 1.0, nf - 1.0).map_err(|e| Error::Evaluation(e.to_string()))?;
+    let p_value = match alternative {
+        Alternative::TwoSided => 2.0 * (1.0 - t.cdf(statistic.abs())),
+        Alternative::Less => t.cdf(statistic),
+        Alternative::Greater => 1.0 - t.cdf(statistic),
+    };
+
+    Ok(DieboldMarianoResult {
+        statistic,
+        p_value: p_value.clamp(0.0, 1.0),
+        mean_difference: mean,
+        horizon,
+        n,
+    })
+}
+
+/// Opciones del conjunto de confianza de modelos
+#[derive(Debug, Clone)]
+pub struct McsOptions {
+    pub alpha: f64,                  // Nivel de la prueba de eliminacion
+    pub bootstrap: usize,            // Replicas bootstrap
+    pub block_length: Option<usize>, // Bloques circulares; por defecto n^(1/3)
+    pub seed: u64,                   // Semilla de ChaCha8: resultados estables entre versiones
+}
+
+impl Default for McsOptions {
+    fn default() -> Self {
+        McsOptions {
+            alpha: 0.1,
+            bootstrap: 1000,
+            block_length: None,
+            seed: 42,
+        }
+    }
+}
+
+/// Resultado del conjunto de confianza de modelos
+#[derive(Debug, Clone)]
+pub struct McsResult {
+    pub included: Vec<usize>, // Indices de los modelos que quedan en el conjunto
+    pub eliminated: Vec<usize>, // En el orden en que se eliminaron
+    pub p_values: Vec<f64>,   // p-valor MCS de cada modelo (en el orden de entrada)
+}
+
+/// Conjunto de confianza de modelos (Hansen, Lunde y Nason, 2011)
+///
+/// Usa el estadistico T_max con bootstrap de bloques circulares. En cada
+/// paso se prueba si todos los modelos restantes tienen igual perdida
+/// esperada; si se rechaza se elimina el de peor perdida relativa.
+///
+/// Parameters:
+/// - errors: una serie de errores por modelo, todas del mismo largo
+/// - loss: funcion de perdida
+/// - options: nivel, replicas, largo de bloque y semilla
+pub fn model_confidence_set(
+    errors: &[Vec<f64>],
+    loss: LossFunction,
+    options: &McsOptions,
+) -> Result<McsResult> {
+    let m = errors.len();
+    if m < 2 {
+        return Err(Error::Evaluation(
+            "Model confidence set needs at least two models".into(),
+        ));
+    }
+    let n = errors[0].len();
+    if errors.iter().any(|e| e.len() != n) {
+        return Err(Error::Evaluation(
+            "All error series must have the same length".into(),
+        ));
+    }
+    if n < 2 || options.bootstrap == 0 {
+        return Err(Error::Evaluation(
+            "Model confidence set needs at least two errors and one bootstrap replica".into(),
+        ));
+    }
+    if !(options.alpha > 0.0 && options.alpha < 1.0) {
+        return Err(Error::Evaluation("Alpha must be in (0, 1)".into()));
+    }
+
+    let losses: Vec<Vec<f64>> = errors
+        .iter()
+        .map(|e| e.iter().map(|x| loss.apply(*x)).collect())
+        .collect();
+    let mean_loss: Vec<f64> = losses
+        .iter()
+        .map(|l| l.iter().sum::<f64>() / n as f64)
+        .collect();
+
+    // Perdida media de cada modelo en cada replica; los mismos indices para
+    // todos los modelos conservan la correlacion entre ellos
+    let block = options
+        .block_length
+        .unwrap_or_else(|| (n as f64).cbrt().ceil() as usize)
+        .clamp(1, n);
+    let mut rng = ChaCha8Rng::seed_from_u64(options.seed);
+    let boot_loss: Vec<Vec<f64>> = (0..options.bootstrap)
+        .map(|_| {
+            let mut indices = Vec::with_capacity(n);
+            while indices.len() < n {
+                let start = rng.random_range(0..n);
+                indices.extend((0..block).map(|k| (start + k) % n).take(n - indices.len()));
+            }
+            losses
+                .iter()
+                .map(|l| indices.iter().map(|&t| l[t]).sum::<f64>() / n as f64)
+                .collect()
+        })
+        .collect();
+
+    let mut remaining: Vec<usize> = (0..m).collect();
+    let mut eliminated = Vec::new();
+    let mut p_values = vec![1.0; m];
+    let mut running_p: f64 = 0.0;
+
+    while remaining.len() > 1 {
+        let k = remaining.len() as f64;
+        let centre = remaining.iter().map(|&i| mean_loss[i]).sum::<f64>() / k;
+
+        // Desviacion bootstrap de la perdida relativa de cada modelo
+        let deviations: Vec<Vec<f64>> = boot_loss
+            .iter()
+            .map(|b| {
+                let boot_centre = remaining.iter().map(|&i| b[i]).sum::<f64>() / k;
+                remaining
+                    .iter()
+                    .map(|&i| (b[i] - boot_centre) - (mean_loss[i] - centre))
+                    .collect()
+            })
+            .collect();
+        let std_dev: Vec<f64> = (0..remaining.len())
+            .map(|j| {
+                let var =
+                    deviations.iter().map(|d| d[j] * d[j]).sum::<f64>() / options.bootstrap as f64;
+                var.sqrt().max(f64::EPSILON)
+            })
+            .collect();
+
+        let t_stats: Vec<f64> = remaining
+            .iter()
+            .enumerate()
+            .map(|(j, &i)| (mean_loss[i] - centre) / std_dev[j])
+            .collect();
+        let (worst, t_max) = t_stats
+            .iter()
+            .copied()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap_or((0, 0.0));
+        let exceed = deviations
+            .iter()
+            .filter(|d| {
+                let boot_max = d
+                    .iter()
+                    .zip(&std_dev)
+                    .map(|(dev, s)| dev / s)
+                    .fold(f64::NEG_INFINITY, f64::max);
+                boot_max >= t_max
+            })
+            .count();
+        let p_value = exceed as f64 / options.bootstrap as f64;
+
+        // El p-valor MCS es el maximo acumulado de las pruebas anteriores
+        running_p = running_p.max(p_value);
+        if p_value >= options.alpha {
+            break;
+        }
+        let model = remaining.remove(worst);
+        p_values[model] = running_p;
+        eliminated.push(model);
+    }
+    for &i in &remaining {
+        p_values[i] = if remaining.len() == 1 { 1.0 } else { running_p };
+    }
+
+    Ok(McsResult {
+        included: remaining,
+        eliminated,
+        p_values,
+    })
+}
+
+#[test]
+fn test_diebold_mariano_and_mcs_separate_models() {
+    use super::test_util::Lcg;
+    let mut rng = Lcg::new(2024);
+    let n = 200;
+    let good: Vec<f64> = (0..n).map(|_| rng.noise()).collect();
+    let similar: Vec<f64> = (0..n).map(|_| rng.noise()).collect();
+    let bad: Vec<f64> = (0..n).map(|_| 3.0 * rng.noise()).collect();
+
+    let result = diebold_mariano(&good, &bad, 1, LossFunction::Squared, Alternative::Less).unwrap();
+    assert!(result.mean_difference < 0.0);
+    assert!(result.p_value < 0.01);
+    let same = diebold_mariano(
+        &good,
+        &similar,
+        3,
+        LossFunction::Absolute,
+        Alternative::TwoSided,
+    )
+    .unwrap();
+    assert!(same.p_value > 0.05);
+
+    // Referencia a mano con h = 2: d = [3, 3, 0, 8, 8], media 4.4,
+    // gamma0 = 9.84 y gamma1 = 1.048, asi que la varianza es 11.936;
+    // DM = 4.4 / sqrt(11.936 / 5) = 2.8478 y la correccion de Harvey,
+    // Leybourne y Newbold es sqrt((5 + 1 - 4 + 2 / 5) / 5) = sqrt(0.48)
+    let hand = diebold_mariano(
+        &[2.0, 2.0, 1.0, -3.0, -3.0],
+        &[1.0; 5],
+        2,
+        LossFunction::Squared,
+        Alternative::Greater,
+    )
+    .unwrap();
+    assert!((hand.mean_difference - 4.4).abs() < 1e-12);
+    assert!((hand.statistic - 1.973008209334639).abs() < 1e-9);
+
+    let mcs = model_confidence_set(
+        &[good, bad, similar],
+        LossFunction::Squared,
+        &McsOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(mcs.eliminated, vec![1]);
+    assert_eq!(mcs.included, vec![0, 2]);
+    assert!(mcs.p_values[1] < 0.1 && mcs.p_values[0] >= 0.1);
+}
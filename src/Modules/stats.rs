@@ -0,0 +1,572 @@
+use super::data_struct::{Dataset, extract_series};
+use super::error::{Error, Result};
+use nalgebra::{DMatrix, DVector};
+use statrs::distribution::{ContinuousCDF, Normal};
+use std::fmt::{self, Write};
+
+/// Resultado de una prueba de raiz unitaria / estacionariedad
+#[derive(Debug, Clone)]
+pub struct UnitRootResult {
+    pub statistic: f64,
+    pub lags: usize,
+    pub critical_values: Vec<(f64, f64)>, // (nivel de significancia, valor critico)
+    pub p_value: f64, // Interpolado en la tabla; 1.0 si no alcanza el nivel mas alto
+    pub null_rejected: bool, // Rechazo al 5%
+}
+
+impl UnitRootResult {
+    /// Indica si la hipotesis nula se rechaza al nivel `alpha`
+    pub fn rejects_at(&self, alpha: f64) -> bool {
+        self.p_value <= alpha
+    }
+}
+
+// Interpola el p-valor en una tabla de (nivel, valor critico). `upper` indica
+// si la region de rechazo esta en la cola derecha (KPSS) o izquierda (ADF).
+// Un estadistico menos extremo que el nivel mas alto da 1.0 ("p > nivel"),
+// asi `rejects_at` no rechaza en el borde de la tabla.
+fn table_p_value(statistic: f64, table: &[(f64, f64)], upper: bool) -> f64 {
+    // Ordena de menos a mas extremo: el nivel decrece
+    let mut table = table.to_vec();
+    table.sort_by(|a, b| b.0.total_cmp(&a.0));
+    let more_extreme = |x: f64, crit: f64| if upper { x >= crit } else { x <= crit };
+
+    let (first, last) = (table[0], table[table.len() - 1]);
+    if !more_extreme(statistic, first.1) {
+        return 1.0;
+    }
+    if more_extreme(statistic, last.1) {
+        return last.0;
+    }
+    for w in table.windows(2) {
+        let ((a_level, a_crit), (b_level, b_crit)) = (w[0], w[1]);
+        if more_extreme(statistic, a_crit) && !more_extreme(statistic, b_crit) {
+            let t = (statistic - a_crit) / (b_crit - a_crit);
+            return a_level + t * (b_level - a_level);
+        }
+    }
+    last.0
+}
+
+/// Autocorrelacion muestral para los rezagos 1..=max_lag (indice k - 1)
+pub fn acf(series: &[f64], max_lag: usize) -> Result<Vec<f64>> {
+    let n = series.len();
+    if n < 2 || max_lag >= n {
+        return Err(Error::Data(format!(
+            "ACF up to lag {} needs more than {} observations",
+            max_lag, max_lag
+        )));
+    }
+    let mean = series.iter().sum::<f64>() / n as f64;
+    let centered: Vec<f64> = series.iter().map(|y| y - mean).collect();
+    let variance = centered.iter().map(|e| e * e).sum::<f64>();
+    if variance <= 0.0 {
+        return Err(Error::Data(
+            "Autocorrelation of a constant series is undefined".into(),
+        ));
+    }
+    Ok((1..=max_lag)
+        .map(|k| centered.windows(k + 1).map(|w| w[0] * w[k]).sum::<f64>() / variance)
+        .collect())
+}
+
+/// Autocorrelacion parcial para los rezagos 1..=max_lag con la recursion
+/// de Durbin-Levinson sobre la ACF
+pub fn pacf(series: &[f64], max_lag: usize) -> Result<Vec<f64>> {
+    let rho = acf(series, max_lag)?;
+    let mut pacf = Vec::with_capacity(max_lag);
+    let mut phi: Vec<f64> = Vec::with_capacity(max_lag);
+    for k in 0..max_lag {
+        let numerator = rho[k] - (0..k).map(|j| phi[j] * rho[k - j - 1]).sum::<f64>();
+        let denominator = 1.0 - (0..k).map(|j| phi[j] * rho[j]).sum::<f64>();
+        let last = if denominator.abs() > 1e-12 {
+            numerator / denominator
+        } else {
+            0.0
+        };
+        let previous = phi.clone();
+        for j in 0..k {
+            phi[j] = previous[j] - last * previous[k - j - 1];
+        }
+        phi.push(last);
+        pacf.push(last);
+    }
+    Ok(pacf)
+}
+
+// Cuantil normal bilateral para un nivel de confianza
+fn normal_quantile(level: f64) -> Result<f64> {
+    if !(level > 0.0 && level < 1.0) {
+        return Err(Error::Data("Confidence level must be in (0, 1)".into()));
+    }
+    let normal = Normal::standard();
+    Ok(normal.inverse_cdf(0.5 + level / 2.0))
+}
+
+/// Banda de confianza bajo ruido blanco: z / sqrt(n)
+///
+/// Sirve para la PACF y para la ACF cuando se contrasta ruido blanco.
+pub fn white_noise_band(n: usize, level: f64) -> Result<f64> {
+    if n == 0 {
+        return Err(Error::Data("Confidence band needs observations".into()));
+    }
+    Ok(normal_quantile(level)? / (n as f64).sqrt())
+}
+
+/// Bandas de Bartlett para la ACF (indice k - 1)
+///
+/// En el rezago k supone un MA(k - 1), asi que la banda se ensancha con las
+/// autocorrelaciones anteriores: z * sqrt((1 + 2 sum_{j<k} r_j^2) / n).
+pub fn acf_bands(acf: &[f64], n: usize, level: f64) -> Result<Vec<f64>> {
+    let z = white_noise_band(n, level)?;
+    let mut cumulative: f64 = 0.0;
+    Ok(acf
+        .iter()
+        .map(|r| {
+            let band = z * (1.0 + 2.0 * cumulative).sqrt();
+            cumulative += r * r;
+            band
+        })
+        .collect())
+}
+
+/// Numero de rezagos por defecto para la varianza de largo plazo
+pub fn default_lags(n: usize) -> usize {
+    (4.0 * (n as f64 / 100.0).powf(0.25)).trunc() as usize
+}
+
+// Varianza de largo plazo con kernel de Bartlett (Newey-West)
+fn long_run_variance(residuals: &[f64], lags: usize) -> f64 {
+    let n = residuals.len() as f64;
+    let mut variance = residuals.iter().map(|e| e * e).sum::<f64>() / n;
+    for l in 1..=lags.min(residuals.len() - 1) {
+        let weight = 1.0 - l as f64 / (lags as f64 + 1.0);
+        let cov = residuals.windows(l + 1).map(|w| w[0] * w[l]).sum::<f64>() / n;
+        variance += 2.0 * weight * cov;
+    }
+    variance
+}
+
+/// Prueba KPSS de estacionariedad en nivel
+///
+/// H0: la serie es estacionaria. Un rechazo sugiere diferenciar.
+pub fn kpss(series: &[f64], lags: Option<usize>) -> Result<UnitRootResult> {
+    let n = series.len();
+    if n < 10 {
+        return Err(Error::Data(
+            "KPSS test needs at least 10 observations".into(),
+        ));
+    }
+    let lags = lags.unwrap_or_else(|| default_lags(n));
+
+    let mean = series.iter().sum::<f64>() / n as f64;
+    let residuals: Vec<f64> = series.iter().map(|y| y - mean).collect();
+
+    let mut partial = 0.0;
+    let mut sum_partial_sq = 0.0;
+    for e in &residuals {
+        partial += e;
+        sum_partial_sq += partial * partial;
+    }
+
+    let variance = long_run_variance(&residuals, lags);
+    let statistic = if variance > 0.0 {
+        sum_partial_sq / ((n * n) as f64 * variance)
+    } else {
+        0.0 // Serie constante: estacionaria de forma trivial
+    };
+
+    // Kwiatkowski et al. (1992), tabla 1, caso con constante
+    let critical_values = vec![(0.10, 0.347), (0.05, 0.463), (0.025, 0.574), (0.01, 0.739)];
+    let p_value = table_p_value(statistic, &critical_values, true);
+
+    Ok(UnitRootResult {
+        statistic,
+        lags,
+        null_rejected: p_value <= 0.05,
+        critical_values,
+        p_value,
+    })
+}
+
+// Superficie de respuesta de MacKinnon (2010) para el caso con constante,
+// compartida por ADF y Phillips-Perron
+fn mackinnon_critical_values(nobs: usize) -> Vec<(f64, f64)> {
+    let nf = nobs as f64;
+    let mackinnon = |b0: f64, b1: f64, b2: f64| b0 + b1 / nf + b2 / (nf * nf);
+    vec![
+        (0.01, mackinnon(-3.43035, -6.5393, -16.786)),
+        (0.05, mackinnon(-2.86154, -2.8903, -4.234)),
+        (0.10, mackinnon(-2.56677, -1.5384, -2.809)),
+    ]
+}
+
+// Estima y = X b por minimos cuadrados y devuelve (b, errores estandar)
+pub(crate) fn ols(x: &DMatrix<f64>, y: &DVector<f64>) -> Option<(DVector<f64>, DVector<f64>)> {
+    let xtx_inv = (x.transpose() * x).try_inverse()?;
+    let beta = &xtx_inv * (x.transpose() * y);
+    let residuals = y - x * &beta;
+    let dof = x.nrows().checked_sub(x.ncols()).filter(|&d| d > 0)? as f64;
+    let sigma2 = residuals.norm_squared() / dof;
+    let se = xtx_inv.diagonal().map(|v| (v * sigma2).sqrt());
+    Some((beta, se))
+}
+
+/// Prueba aumentada de Dickey-Fuller con constante
+///
+/// H0: la serie tiene raiz unitaria. Un rechazo indica que ya es estacionaria.
+pub fn adf(series: &[f64], lags: Option<usize>) -> Result<UnitRootResult> {
+    let n = series.len();
+    let lags = lags.unwrap_or_else(|| ((n as f64 - 1.0).cbrt()).trunc() as usize);
+    if n < lags + 10 {
+        return Err(Error::Data(
+            "ADF test needs more observations than lags".into(),
+        ));
+    }
+
+    let diff: Vec<f64> = series.windows(2).map(|w| w[1] - w[0]).collect();
+    let rows = diff.len() - lags;
+    let cols = 2 + lags;
+
+    // dy_t = a + g * y_{t-1} + sum d_i * dy_{t-i}
+    let mut x = DMatrix::zeros(rows, cols);
+    let mut y = DVector::zeros(rows);
+    for r in 0..rows {
+        let t = r + lags;
+        y[r] = diff[t];
+        x[(r, 0)] = 1.0;
+        x[(r, 1)] = series[t];
+        for i in 1..=lags {
+            x[(r, 1 + i)] = diff[t - i];
+        }
+    }
+
+    let (beta, se) = ols(&x, &y)
+        .ok_or_else(|| Error::Data("ADF regression is singular (constant series?)".into()))?;
+    let statistic = beta[1] / se[1];
+
+    let critical_values = mackinnon_critical_values(rows);
+    let p_value = table_p_value(statistic, &critical_values, false);
+
+    Ok(UnitRootResult {
+        statistic,
+        lags,
+        null_rejected: p_value <= 0.05,
+        critical_values,
+        p_value,
+    })
+}
+
+/// Prueba de Phillips-Perron (Z_tau) con constante
+///
+/// H0: raiz unitaria, como ADF, pero la autocorrelacion de los errores se
+/// corrige con la varianza de largo plazo en vez de con rezagos de la
+/// diferencia. Usa los mismos valores criticos de MacKinnon.
+pub fn phillips_perron(series: &[f64], lags: Option<usize>) -> Result<UnitRootResult> {
+    let n = series.len();
+    if n < 10 {
+        return Err(Error::Data(
+            "Phillips-Perron test needs at least 10 observations".into(),
+        ));
+    }
+    let lags = lags.unwrap_or_else(|| default_lags(n));
+
+    // y_t = a + rho * y_{t-1} + u_t
+    let rows = n - 1;
+    let x = DMatrix::from_fn(rows, 2, |r, c| if c == 0 { 1.0 } else { series[r] });
+    let y = DVector::from_fn(rows, |r, _| series[r + 1]);
+    let (beta, se) = ols(&x, &y).ok_or_else(|| {
+        Error::Data("Phillips-Perron regression is singular (constant series?)".into())
+    })?;
+    let residuals: Vec<f64> = (&y - &x * &beta).iter().copied().collect();
+
+    let nf = rows as f64;
+    let gamma0 = residuals.iter().map(|u| u * u).sum::<f64>() / nf;
+    let s2 = gamma0 * nf / (nf - 2.0);
+    let lambda2 = long_run_variance(&residuals, lags);
+    if gamma0 <= 0.0 || lambda2 <= 0.0 {
+        return Err(Error::Data(
+            "Phillips-Perron residual variance is not positive".into(),
+        ));
+    }
+
+    // Hamilton (1994), ec. 17.6.12
+    let t_rho = (beta[1] - 1.0) / se[1];
+    let statistic = (gamma0 / lambda2).sqrt() * t_rho
+        - (lambda2 - gamma0) / (2.0 * lambda2.sqrt()) * (nf * se[1] / s2.sqrt());
+
+    let critical_values = mackinnon_critical_values(rows);
+    let p_value = table_p_value(statistic, &critical_values, false);
+
+    Ok(UnitRootResult {
+        statistic,
+        lags,
+        null_rejected: p_value <= 0.05,
+        critical_values,
+        p_value,
+    })
+}
+
+/// Analisis de autocorrelacion y estacionariedad de una serie
+#[derive(Debug, Clone)]
+pub struct SeriesAnalysis {
+    pub name: String,
+    pub n: usize,
+    pub level: f64,          // Nivel de confianza de las bandas
+    pub acf: Vec<f64>,       // Rezagos 1..=max_lag
+    pub acf_bands: Vec<f64>, // Bandas de Bartlett por rezago
+    pub pacf: Vec<f64>,      // Rezagos 1..=max_lag
+    pub pacf_band: f64,      // Banda de ruido blanco
+    pub adf: UnitRootResult,
+    pub kpss: UnitRootResult,
+    pub phillips_perron: UnitRootResult,
+}
+
+impl SeriesAnalysis {
+    /// Estacionaria al 5% si ADF y Phillips-Perron rechazan la raiz unitaria
+    /// y KPSS no rechaza la estacionariedad
+    pub fn is_stationary(&self) -> bool {
+        self.adf.null_rejected && self.phillips_perron.null_rejected && !self.kpss.null_rejected
+    }
+
+    /// Rezagos cuya ACF sale de su banda (candidatos para el orden MA)
+    pub fn significant_acf_lags(&self) -> Vec<usize> {
+        significant_lags(self.acf.iter().zip(&self.acf_bands))
+    }
+
+    /// Rezagos cuya PACF sale de la banda (candidatos para el orden AR)
+    pub fn significant_pacf_lags(&self) -> Vec<usize> {
+        significant_lags(self.pacf.iter().zip(std::iter::repeat(&self.pacf_band)))
+    }
+
+    /// Reporte en formato largo: una fila por rezago de ACF/PACF y por prueba
+    ///
+    /// Columnas: series, statistic, lag, value, threshold, p_value. En ACF y
+    /// PACF `threshold` es la banda; en las pruebas, el valor critico al 5%.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("series,statistic,lag,value,threshold,p_value\n");
+        for (k, (r, band)) in self.acf.iter().zip(&self.acf_bands).enumerate() {
+            let _ = writeln!(out, "{},acf,{},{},{},", self.name, k + 1, r, band);
+        }
+        for (k, r) in self.pacf.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "{},pacf,{},{},{},",
+                self.name,
+                k + 1,
+                r,
+                self.pacf_band
+            );
+        }
+        for (label, test) in self.tests() {
+            let _ = writeln!(
+                out,
+                "{},{},{},{},{},{}",
+                self.name,
+                label,
+                test.lags,
+                test.statistic,
+                critical_at(test, 0.05),
+                test.p_value
+            );
+        }
+        out
+    }
+
+    /// Escribe `to_csv` en un archivo
+    pub fn write_csv(&self, path: &str) -> Result<()> {
+        std::fs::write(path, self.to_csv())?;
+        Ok(())
+    }
+
+    fn tests(&self) -> [(&'static str, &UnitRootResult); 3] {
+        [
+            ("adf", &self.adf),
+            ("kpss", &self.kpss),
+            ("phillips_perron", &self.phillips_perron),
+        ]
+    }
+}
+
+fn significant_lags<'a>(pairs: impl Iterator<Item = (&'a f64, &'a f64)>) -> Vec<usize> {
+    pairs
+        .enumerate()
+        .filter(|(_, (r, band))| r.abs() > **band)
+        .map(|(k, _)| k + 1)
+        .collect()
+}
+
+fn critical_at(test: &UnitRootResult, level: f64) -> f64 {
+    test.critical_values
+        .iter()
+        .find(|(l, _)| (l - level).abs() < 1e-12)
+        .map_or(f64::NAN, |(_, c)| *c)
+}
+
+impl fmt::Display for SeriesAnalysis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Serie: {} ({} observaciones)", self.name, self.n)?;
+        writeln!(
+            f,
+            "Rezago   ACF       Banda     PACF      Banda ({:.0}%)",
+            100.0 * self.level
+        )?;
+        for (k, ((r, band), p)) in self
+            .acf
+            .iter()
+            .zip(&self.acf_bands)
+            .zip(&self.pacf)
+            .enumerate()
+        {
+            let mark = |v: f64, b: f64| if v.abs() > b { '*' } else { ' ' };
+            writeln!(
+                f,
+                "{:<8} {:>8.4}{} {:>8.4} {:>8.4}{} {:>8.4}",
+                k + 1,
+                r,
+                mark(*r, *band),
+                band,
+                p,
+                mark(*p, self.pacf_band),
+                self.pacf_band
+            )?;
+        }
+        writeln!(
+            f,
+            "Prueba            Estadistico  Rezagos  p-valor  Crit. 5%  H0"
+        )?;
+        for (label, test, null) in [
+            ("ADF", &self.adf, "raiz unitaria"),
+            ("KPSS", &self.kpss, "estacionaria"),
+            ("Phillips-Perron", &self.phillips_perron, "raiz unitaria"),
+        ] {
+            // Fuera de la tabla solo se sabe que el p-valor supera su nivel mas alto
+            let top = test.critical_values.iter().map(|c| c.0).fold(0.0, f64::max);
+            let p_value = if test.p_value > top {
+                format!("> {:.2}", top)
+            } else {
+                format!("{:.3}", test.p_value)
+            };
+            writeln!(
+                f,
+                "{:<17} {:>11.4}  {:>7}  {:>7}  {:>8.4}  {} ({})",
+                label,
+                test.statistic,
+                test.lags,
+                p_value,
+                critical_at(test, 0.05),
+                null,
+                if test.null_rejected {
+                    "rechazada"
+                } else {
+                    "no rechazada"
+                }
+            )?;
+        }
+        writeln!(
+            f,
+            "Conclusion: {}",
+            if self.is_stationary() {
+                "estacionaria"
+            } else {
+                "no estacionaria o no concluyente; considerar diferenciar"
+            }
+        )
+    }
+}
+
+/// Analiza una serie: ACF/PACF con bandas al 95% y las tres pruebas
+///
+/// Parameters:
+/// - name: nombre usado en el reporte
+/// - series: la serie a analizar
+/// - max_lag: rezagos de ACF/PACF; por defecto min(10 log10(n), n / 4)
+pub fn analyze_series(
+    name: &str,
+    series: &[f64],
+    max_lag: Option<usize>,
+) -> Result<SeriesAnalysis> {
+    let n = series.len();
+    let max_lag = max_lag
+        .unwrap_or_else(|| ((10.0 * (n as f64).log10()) as usize).min(n / 4))
+        .max(1);
+    let level = 0.95;
+    let acf = acf(series, max_lag)?;
+    Ok(SeriesAnalysis {
+        name: name.to_string(),
+        n,
+        level,
+        acf_bands: acf_bands(&acf, n, level)?,
+        acf,
+        pacf: pacf(series, max_lag)?,
+        pacf_band: white_noise_band(n, level)?,
+        adf: adf(series, None)?,
+        kpss: kpss(series, None)?,
+        phillips_perron: phillips_perron(series, None)?,
+    })
+}
+
+/// Analiza una columna de un `Dataset` (ver `extract_series`)
+pub fn analyze_column(
+    data: &Dataset,
+    field: &str,
+    max_lag: Option<usize>,
+) -> Result<SeriesAnalysis> {
+    analyze_series(field, &extract_series(data, field)?, max_lag)
+}
+
+#[test]
+fn test_unit_root_tests_on_random_walk() {
+    use super::test_util::Lcg;
+
+    // Caminata aleatoria deterministica: KPSS rechaza, ADF no
+    let mut rng = Lcg::new(12345);
+    let mut walk = Vec::with_capacity(500);
+    let mut level = 0.0;
+    for _ in 0..500 {
+        level += rng.noise();
+        walk.push(level);
+    }
+    assert!(kpss(&walk, None).unwrap().null_rejected);
+    assert!(!adf(&walk, None).unwrap().null_rejected);
+    assert!(!phillips_perron(&walk, None).unwrap().null_rejected);
+    // Sin rechazo tampoco al 10%: el p-valor queda por encima de la tabla
+    let pp = phillips_perron(&walk, None).unwrap();
+    assert!(!pp.rejects_at(0.10) && pp.p_value > 0.10);
+    assert!(!adf(&walk, None).unwrap().rejects_at(0.10));
+
+    let steps: Vec<f64> = walk.windows(2).map(|w| w[1] - w[0]).collect();
+    assert!(!kpss(&steps, None).unwrap().rejects_at(0.10));
+    assert!(adf(&steps, None).unwrap().null_rejected);
+    assert!(phillips_perron(&steps, None).unwrap().null_rejected);
+}
+
+#[test]
+fn test_series_analysis_report() {
+    use super::test_util::Lcg;
+
+    // AR(1) con phi = 0.7: ACF geometrica y PACF que se corta en el rezago 1
+    let mut rng = Lcg::new(99);
+    let mut value = 0.0;
+    let series: Vec<f64> = (0..400)
+        .map(|_| {
+            value = 0.7 * value + rng.noise();
+            value
+        })
+        .collect();
+    let analysis = analyze_series("ar1", &series, Some(8)).unwrap();
+    assert!(analysis.is_stationary());
+    assert_eq!(analysis.significant_pacf_lags(), vec![1]);
+    assert!((analysis.acf[1] - 0.49).abs() < 0.1);
+    assert!((analysis.pacf_band - 1.96 / 20.0).abs() < 1e-3);
+    // Las bandas de Bartlett solo se ensanchan
+    assert!(analysis.acf_bands.windows(2).all(|w| w[1] >= w[0]));
+    assert_eq!(analysis.acf_bands[0], analysis.pacf_band);
+
+    let csv = analysis.to_csv();
+    assert_eq!(csv.lines().count(), 1 + 8 + 8 + 3);
+    assert!(csv.lines().any(|l| l.starts_with("ar1,phillips_perron,")));
+    assert!(analysis.to_string().contains("Conclusion: estacionaria"));
+    assert!(!analysis.kpss.rejects_at(0.10));
+    assert!(analysis.to_string().contains("> 0.10"));
+}
@@ -0,0 +1,29 @@
+// Utilidades compartidas por los tests de los modulos
+
+/// Generador congruencial lineal para ruido de prueba reproducible
+///
+/// Deterministico y sin dependencias, asi los datos de los tests no cambian
+/// con la version de `rand`.
+pub(crate) struct Lcg(u64);
+
+impl Lcg {
+    pub(crate) fn new(seed: u64) -> Self {
+        Lcg(seed)
+    }
+
+    /// Ruido uniforme en [-0.5, 0.5)
+    pub(crate) fn noise(&mut self) -> f64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((self.0 >> 33) as f64 / (1u64 << 31) as f64) - 0.5
+    }
+
+    /// Ruido normal estandar por Box-Muller
+    pub(crate) fn gaussian(&mut self) -> f64 {
+        let u1 = 0.5 - self.noise(); // En (0, 1], evita ln(0)
+        let u2 = self.noise() + 0.5;
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+}
@@ -0,0 +1,212 @@
+use super::data_struct::Dataset;
+use super::error::{Error, Result};
+use std::time::{Duration, SystemTime};
+
+/// Un contador `millis()` de 32 bits da la vuelta cada 2^32 ms (~49.7 dias)
+pub const U32_MILLIS_WRAP: u64 = 1 << 32;
+
+// Cuantos periodos puede durar el salto de una vuelta del contador para que
+// se considere wraparound y no un reinicio del microcontrolador
+const WRAP_TOLERANCE_PERIODS: u64 = 10;
+
+/// Hueco en el muestreo: filas que deberian existir y no llegaron
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gap {
+    pub index: usize,   // Primera fila despues del hueco
+    pub start_ms: u64,  // Tiempo transcurrido de la ultima fila antes del hueco
+    pub end_ms: u64,    // Tiempo transcurrido de la fila `index`
+    pub missing: usize, // Muestras estimadas que faltan
+}
+
+/// Indice de tiempo para logs con contador de milisegundos desde el arranque
+///
+/// Convierte el contador crudo en un tiempo transcurrido continuo, corrigiendo
+/// los reinicios del nodo y el desbordamiento de `millis()` en 32 bits
+#[derive(Debug, Clone)]
+pub struct TimeIndex {
+    raw: Vec<u64>,
+    elapsed_ms: Vec<u64>,
+    period_ms: u64,
+    resets: Vec<usize>,
+    wraparounds: Vec<usize>,
+    start: Option<SystemTime>,
+}
+
+impl TimeIndex {
+    /// Construye el indice a partir del contador crudo
+    ///
+    /// Parameters:
+    /// - raw: valores de `millis()` en el orden en que se registraron
+    ///
+    /// Returns:
+    /// - El indice con periodo detectado, reinicios y vueltas del contador
+    pub fn from_millis(raw: &[u64]) -> Result<TimeIndex> {
+        if raw.len() < 2 {
+            return Err(Error::Data(
+                "At least two timestamps are needed to build a time index".into(),
+            ));
+        }
+
+        let period_ms = detect_period(raw)
+            .ok_or_else(|| Error::Data("Could not detect a sampling period".into()))?;
+
+        let mut elapsed_ms = Vec::with_capacity(raw.len());
+        let mut resets = Vec::new();
+        let mut wraparounds = Vec::new();
+        elapsed_ms.push(0);
+
+        for i in 1..raw.len() {
+            let (prev, current) = (raw[i - 1], raw[i]);
+            let last = elapsed_ms[i - 1];
+
+            let step = if current >= prev {
+                current - prev
+            } else if prev < U32_MILLIS_WRAP
+                && current + U32_MILLIS_WRAP - prev <= WRAP_TOLERANCE_PERIODS * period_ms
+            {
+                wraparounds.push(i);
+                current + U32_MILLIS_WRAP - prev
+            } else {
+                // Reinicio: el tiempo apagado es desconocido, pero al menos
+                // paso lo que marca el contador desde el nuevo arranque
+                resets.push(i);
+                current.max(period_ms)
+            };
+
+            elapsed_ms.push(last + step);
+        }
+
+        Ok(TimeIndex {
+            raw: raw.to_vec(),
+            elapsed_ms,
+            period_ms,
+            resets,
+            wraparounds,
+            start: None,
+        })
+    }
+
+    /// Construye el indice desde la columna de tiempo de un dataset
+    pub fn from_dataset(data: &Dataset) -> Result<TimeIndex> {
+        let raw = data
+            .timestamps()
+            .ok_or_else(|| Error::Data("Dataset has no timestamp column".into()))?;
+        TimeIndex::from_millis(raw)
+    }
+
+    /// Ancla el indice a la hora real de la primera muestra
+    pub fn with_start(mut self, start: SystemTime) -> TimeIndex {
+        self.start = Some(start);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.raw.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.raw.is_empty()
+    }
+
+    /// Periodo de muestreo detectado (mediana de los incrementos)
+    pub fn period_ms(&self) -> u64 {
+        self.period_ms
+    }
+
+    pub fn period_secs(&self) -> f64 {
+        self.period_ms as f64 / 1000.0
+    }
+
+    /// Contador tal como venia en el archivo
+    pub fn raw(&self) -> &[u64] {
+        &self.raw
+    }
+
+    /// Milisegundos transcurridos desde la primera muestra, siempre crecientes
+    pub fn elapsed_ms(&self) -> &[u64] {
+        &self.elapsed_ms
+    }
+
+    pub fn elapsed_secs(&self) -> Vec<f64> {
+        self.elapsed_ms.iter().map(|&t| t as f64 / 1000.0).collect()
+    }
+
+    /// Filas donde el contador volvio a empezar por un reinicio del nodo
+    pub fn resets(&self) -> &[usize] {
+        &self.resets
+    }
+
+    /// Filas donde `millis()` desbordo los 32 bits
+    pub fn wraparounds(&self) -> &[usize] {
+        &self.wraparounds
+    }
+
+    /// Huecos donde el incremento supera `tolerance` veces el periodo
+    pub fn gaps(&self, tolerance: f64) -> Vec<Gap> {
+        let limit = self.period_ms as f64 * tolerance;
+        self.elapsed_ms
+            .windows(2)
+            .enumerate()
+            .filter(|(_, w)| (w[1] - w[0]) as f64 > limit)
+            .map(|(i, w)| {
+                let steps = ((w[1] - w[0]) as f64 / self.period_ms as f64).round() as usize;
+                Gap {
+                    index: i + 1,
+                    start_ms: w[0],
+                    end_ms: w[1],
+                    missing: steps.saturating_sub(1),
+                }
+            })
+            .collect()
+    }
+
+    /// `true` si no hay reinicios ni huecos mayores a `tolerance` periodos
+    pub fn is_regular(&self, tolerance: f64) -> bool {
+        self.resets.is_empty() && self.gaps(tolerance).is_empty()
+    }
+
+    /// Hora real de la fila `i`, solo si el indice fue anclado
+    pub fn wall_clock(&self, i: usize) -> Option<SystemTime> {
+        let start = self.start?;
+        let elapsed = *self.elapsed_ms.get(i)?;
+        Some(start + Duration::from_millis(elapsed))
+    }
+}
+
+// Mediana de los incrementos positivos (ignora reinicios y vueltas)
+fn detect_period(raw: &[u64]) -> Option<u64> {
+    let mut steps: Vec<u64> = raw
+        .windows(2)
+        .filter(|w| w[1] > w[0])
+        .map(|w| w[1] - w[0])
+        .collect();
+    if steps.is_empty() {
+        return None;
+    }
+    steps.sort_unstable();
+    Some(steps[steps.len() / 2])
+}
+
+#[test]
+fn test_time_index_resets_wraps_and_gaps() {
+    let wrap = U32_MILLIS_WRAP;
+    let raw = [
+        wrap - 20_000,
+        wrap - 10_000,
+        5,      // wraparound
+        10_005, // normal
+        40_005, // hueco de dos muestras
+        50,     // reinicio
+        10_050,
+    ];
+    let index = TimeIndex::from_millis(&raw).unwrap();
+    assert_eq!(index.period_ms(), 10_000);
+    assert_eq!(index.wraparounds(), [2]);
+    assert_eq!(index.resets(), [5]);
+    assert_eq!(index.elapsed_ms()[2], 20_005);
+
+    let gaps = index.gaps(1.5);
+    assert_eq!(gaps.len(), 1);
+    assert_eq!(gaps[0].index, 4);
+    assert_eq!(gaps[0].missing, 2);
+}
@@ -0,0 +1,4 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[allow(non_snake_case)]
+pub mod Modules;
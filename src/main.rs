@@ -1,10 +1,11 @@
-mod Modules;
-use Modules::sa::recocido_simulado;
-use Modules::{arima, arima_eval, data_struct};
-use arima::{ArimaModel, analyze_time_series};
+use arima::ArimaSpec;
 use arima_eval::{WeightingStrategy, calculate_weighted_comparison, generate_weights};
+use embebidos_limpo::Modules::sa::recocido_simulado;
+use embebidos_limpo::Modules::{arima, arima_eval, data_struct, fixed_point, forecaster, metrics};
 
-use data_struct::{Data, extract_series, read_csv};
+use data_struct::{extract_series, read_csv};
+use forecaster::{Forecaster, ForecasterSpec, evaluate_holdout};
+use metrics::EvaluationOptions;
 use std::error::Error;
 
 fn main() {
@@ -12,6 +13,15 @@ fn main() {
     println!("Solucion encontrada: x = {}, f(x) = {}", solucion, valor);
 }
 
+// Muestra un pronostico de `steps` pasos de cualquier modelo
+fn print_forecast(model: &dyn Forecaster, steps: usize, unit: &str) {
+    println!("\n{}-step forecast with {}:", steps, model.name());
+    for (i, value) in model.forecast(steps).iter().enumerate() {
+        println!("Step {}: {:.2}{}", i + 1, value, unit);
+    }
+}
+
+#[allow(dead_code)]
 fn practica8() -> Result<(), Box<dyn Error>> {
     // Ruta del archivo CSV con los datos
     let filepath = "sensor_data.csv";
@@ -23,105 +33,76 @@ fn practica8() -> Result<(), Box<dyn Error>> {
         "Datos cargados exitosamente: {} puntos de datos",
         data.len()
     );
+    let series = extract_series(&data, "Temperature")?;
 
-    // Configuración de los parámetros del modelo ARIMA
-    let p = 2; // Orden autoregresivo (AR)
-    let d = 1; // Orden de diferenciación
-    let q = 1; // Orden de media móvil (MA)
-
-    // División de datos en entrenamiento y prueba
-    let train_size = (data.len() as f64 * 0.8) as usize;
-    let train_data = data[0..train_size].to_vec();
-    let test_data = data[train_size..].to_vec();
-
-    // Extraer valores reales de prueba para comparación
-    let actual_values = extract_series(&test_data, "Temperature");
-
-    // Ajustar el modelo ARIMA con los datos de entrenamiento
+    // Configuración del modelo ARIMA(p, d, q): AR, diferenciación y MA
+    let spec = ArimaSpec::new(2, 1, 1);
     println!(
         "Ajustando modelo ARIMA({}, {}, {}) para la temperatura...",
-        p, d, q
+        spec.p, spec.d, spec.q
     );
 
-    match ArimaModel::fit(&train_data, "Temperature", p, d, q) {
-        Ok(model) => {
-            // Generar pronóstico para el período de prueba
-            let steps = test_data.len();
-            let predicted_values = model.forecast(steps);
-
-            println!("\nEvaluación del modelo:");
-            println!("Número de puntos de prueba: {}", steps);
-
-            // Cálculo de métricas de error básicas
-            let mut total_error = 0.0;
-            let mut total_abs_error = 0.0;
-            let mut total_squared_error = 0.0;
-
-            for i in 0..steps {
-                let error = actual_values[i] - predicted_values[i];
-                total_error += error;
-                total_abs_error += error.abs();
-                total_squared_error += error.powi(2);
-            }
-
-            let me = total_error / steps as f64;
-            let mae = total_abs_error / steps as f64;
-            let rmse = (total_squared_error / steps as f64).sqrt();
-
-            println!("Error medio (ME): {:.4}", me);
-            println!("Error absoluto medio (MAE): {:.4}", mae);
-            println!("Raíz del error cuadrático medio (RMSE): {:.4}", rmse);
-
-            // Comparaciones ponderadas con diferentes estrategias
-            println!("\nComparaciones ponderadas:");
-
-            // 1. Pesos lineales (más peso a valores recientes)
-            let linear_weights = generate_weights(steps, WeightingStrategy::Linear);
-            let linear_result = calculate_weighted_comparison(
-                &actual_values,
-                &predicted_values,
-                Some(&linear_weights),
-            );
-            println!("Ponderación lineal (valores recientes enfatizados):");
-            println!("  MAE ponderado: {:.4}", linear_result.weighted_abs_error);
-            println!("  RMSE ponderado: {:.4}", linear_result.weighted_rmse);
-
-            // 2. Pesos exponenciales
-            let exp_weights = generate_weights(steps, WeightingStrategy::Exponential(0.2));
-            let exp_result = calculate_weighted_comparison(
-                &actual_values,
-                &predicted_values,
-                Some(&exp_weights),
-            );
-            println!("Ponderación exponencial (alpha=0.2):");
-            println!("  MAE ponderado: {:.4}", exp_result.weighted_abs_error);
-            println!("  RMSE ponderado: {:.4}", exp_result.weighted_rmse);
-
-            // 3. Valores ponderados combinados
-            println!("\nValores combinados ponderados:");
-            for i in 0..steps.min(5) {
-                // Mostrar los primeros 5 puntos
-                println!(
-                    "Punto {}: Real={:.2}, Predicho={:.2}, Ponderado={:.2}",
-                    i + 1,
-                    actual_values[i],
-                    predicted_values[i],
-                    linear_result.weighted_values[i]
-                );
-            }
-
-            // Usar los valores ponderados para lógica adicional
-            let weighted_forecast = linear_result.weighted_values;
-            println!("\nEl pronóstico ponderado está listo para su uso en procesos posteriores...");
-        }
+    // Ajuste con el 80% inicial y pronóstico del período de prueba
+    let evaluation = match evaluate_holdout(&spec, &series, 0.8, &EvaluationOptions::default()) {
+        Ok(evaluation) => evaluation,
         Err(e) => {
             eprintln!("Error al ajustar el modelo ARIMA: {}", e);
+            return Ok(());
+        }
+    };
+    let actual_values = &evaluation.actual;
+    let predicted_values = &evaluation.predicted;
+    let steps = actual_values.len();
+
+    println!("\nEvaluación del modelo:");
+    println!("Número de puntos de prueba: {}", steps);
+    print!("{}", evaluation.report);
+
+    // Comparaciones ponderadas con diferentes estrategias
+    println!("\nComparaciones ponderadas:");
+    let mut linear_values = Vec::new();
+    for (label, strategy) in [
+        (
+            "Ponderación lineal (valores recientes enfatizados)",
+            WeightingStrategy::Linear,
+        ),
+        (
+            "Ponderación exponencial (alpha=0.2)",
+            WeightingStrategy::Exponential(0.2),
+        ),
+    ] {
+        let linear = matches!(strategy, WeightingStrategy::Linear);
+        let weights = generate_weights(steps, strategy)?;
+        let result =
+            calculate_weighted_comparison(actual_values, predicted_values, Some(&weights))?;
+        println!("{}:", label);
+        println!("  MAE ponderado: {:.4}", result.weighted_abs_error);
+        println!("  RMSE ponderado: {:.4}", result.weighted_rmse);
+        if linear {
+            linear_values = result.weighted_values;
         }
     }
 
+    // Valores ponderados combinados (primeros 5 puntos)
+    println!("\nValores combinados ponderados:");
+    for i in 0..steps.min(5) {
+        println!(
+            "Punto {}: Real={:.2}, Predicho={:.2}, Ponderado={:.2}",
+            i + 1,
+            actual_values[i],
+            predicted_values[i],
+            linear_values[i]
+        );
+    }
+
+    // Usar los valores ponderados para lógica adicional
+    let _weighted_forecast = linear_values;
+    println!("\nEl pronóstico ponderado está listo para su uso en procesos posteriores...");
+
     Ok(())
 }
 
+#[allow(dead_code)]
 fn practica7() -> Result<(), Box<dyn Error>> {
     // Path to your CSV file
     let filepath = "sensor_data.csv";
@@ -131,49 +112,41 @@ fn practica7() -> Result<(), Box<dyn Error>> {
     let data = read_csv(filepath)?;
     println!("Successfully loaded {} data points", data.len());
 
-    // Configure ARIMA parameters
-    let p = 2; // AR order
-    let d = 1; // Differencing order
-    let q = 1; // MA order
-
-    // Fit ARIMA model for temperature
-    println!("Fitting ARIMA({},{},{}) model for Temperature...", p, d, q);
-    match ArimaModel::fit(&data, "Temperature", p, d, q) {
-        Ok(model) => {
-            // Print model information
-            println!("\nModel Information:");
-            println!("AR coefficients: {:?}", model.ar_coeffs);
-            println!("MA coefficients: {:?}", model.ma_coeffs);
-            println!("AIC: {:.4}", model.aic());
-
-            // Generate forecast
-            let steps = 10;
-            let forecast = model.forecast(steps);
-            println!("\n{}-step Temperature forecast:", steps);
-            for (i, value) in forecast.iter().enumerate() {
-                println!("Step {}: {:.2}°", i + 1, value);
-            }
-
-            // You can also fit a model for humidity
-            println!("\nFitting ARIMA model for Humidity...");
-            if let Ok(humidity_model) = ArimaModel::fit(&data, "Humidity", p, d, q) {
-                let humidity_forecast = humidity_model.forecast(steps);
-                println!("\n{}-step Humidity forecast:", steps);
-                for (i, value) in humidity_forecast.iter().enumerate() {
-                    println!("Step {}: {:.2}%", i + 1, value);
-                }
+    // Same ARIMA(2,1,1) for every sensor column
+    let spec = ArimaSpec::new(2, 1, 1);
+    let steps = 10;
+    for (field, unit) in [("Temperature", "°"), ("Humidity", "%")] {
+        println!(
+            "\nFitting ARIMA({},{},{}) model for {}...",
+            spec.p, spec.d, spec.q, field
+        );
+        match spec.fit_dataset(&data, field) {
+            Ok(model) => {
+                println!("AR coefficients: {:?}", model.ar_coeffs);
+                println!("MA coefficients: {:?}", model.ma_coeffs);
+                println!("AIC: {:.4}", model.aic());
+                print_forecast(&model, steps, unit);
             }
-        }
-        Err(e) => {
-            eprintln!("Error fitting ARIMA model: {}", e);
+            Err(e) => eprintln!("Error fitting model for {}: {}", field, e),
         }
     }
 
     Ok(())
 }
 
+#[allow(dead_code)]
 fn practica9() -> Result<(), Box<dyn Error>> {
     //TODO practica 9
 
     Ok(())
 }
+
+// Error del predictor en punto fijo (Q16.16 y Q15) frente al modelo en f64
+#[allow(dead_code)]
+fn reporte_punto_fijo() -> Result<(), Box<dyn Error>> {
+    println!("Pronostico a un paso en punto fijo contra f64:");
+    for report in fixed_point::fixed_point_report("sensor_data.csv")? {
+        println!("{}", report);
+    }
+    Ok(())
+}